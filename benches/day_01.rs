@@ -0,0 +1,116 @@
+//! Compares ways of finding day 1's spelled out digits, run with `cargo bench --bench day_01`.
+//!
+//! `quadratic` is the original extractor, which tries every prefix of every suffix of a line
+//! against a map of the words. The others use [`Matcher`], either collecting every match or only
+//! looking for the first from the left and the last from the right.
+
+use aoc23::day_01::matcher::{Match, Matcher};
+use aoc23::rng::Rng;
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: usize = 5;
+
+const WORDS: [(&str, i32); 19] = [
+    ("0", 0),
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+fn quadratic(mapper: &HashMap<&str, i32>, line: &str) -> Option<i32> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut nums = Vec::new();
+    let mut partial = String::with_capacity(4);
+
+    for i in 0..chars.len() {
+        partial.clear();
+        for c in &chars[i..] {
+            partial.push(*c);
+            if let Some(&num_word) = mapper.get(partial.as_str()) {
+                nums.push(num_word);
+                break;
+            }
+        }
+    }
+
+    Some(nums.first()? * 10 + nums.last()?)
+}
+
+fn find_all(matcher: &Matcher, line: &str) -> Option<i32> {
+    let matches = matcher.find_all(line);
+    let last_start = matches.last()?.start;
+    let last: &Match = matches.iter().find(|found| found.start == last_start)?;
+    Some(matches.first()?.value * 10 + last.value)
+}
+
+/// Lines of `length` letters that mostly spell out nothing, with the odd word or digit mixed in.
+fn lines(count: usize, length: usize) -> Vec<String> {
+    let mut rng = Rng::new(1);
+    (0..count)
+        .map(|_| {
+            let mut line = String::with_capacity(length + 5);
+            while line.len() < length {
+                match rng.below(8) {
+                    0 => line.push_str(WORDS[rng.below(WORDS.len() as u64) as usize].0),
+                    _ => line.push(char::from(b'a' + rng.below(26) as u8)),
+                }
+            }
+            line
+        })
+        .collect()
+}
+
+/// The fastest of several runs of `extract` over every line, and the total it came to.
+fn time<F: Fn(&str) -> Option<i32>>(lines: &[String], extract: F) -> (Duration, i64) {
+    let mut fastest = Duration::MAX;
+    let mut total = 0;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        total = lines
+            .iter()
+            .map(|line| i64::from(extract(black_box(line)).unwrap_or_default()))
+            .sum();
+        fastest = fastest.min(start.elapsed());
+    }
+    (fastest, total)
+}
+
+fn main() {
+    let mapper: HashMap<&str, i32> = WORDS.iter().copied().collect();
+    let matcher = Matcher::new(&WORDS, false);
+
+    println!(
+        "{:<12}{:>8}{:>16}{:>16}{:>16}",
+        "line length", "lines", "quadratic", "find all", "first and last"
+    );
+    for (count, length) in [(1000, 50), (100, 200), (10, 800)] {
+        let lines = lines(count, length);
+        let (quadratic, want) = time(&lines, |line| quadratic(&mapper, line));
+        let (find_all, total) = time(&lines, |line| find_all(&matcher, line));
+        assert_eq!(want, total, "find all");
+        let (first_and_last, total) = time(&lines, |line| matcher.calibration_value(line));
+        assert_eq!(want, total, "first and last");
+
+        println!(
+            "{:<12}{:>8}{:>16?}{:>16?}{:>16?}",
+            length, count, quadratic, find_all, first_and_last
+        );
+    }
+}
@@ -0,0 +1,302 @@
+//! Accepted answers for the real puzzle inputs, see [`Answers`] and [`check`].
+
+use crate::error::{Error, Result};
+use crate::solution::{Part, PartResult};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const FILE_NAME: &str = "answers.toml";
+
+/// The answers file, one table per day with a key per part:
+///
+/// ```toml
+/// [day_01]
+/// part_one = "142"
+/// part_two = "281"
+/// ```
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct Answers {
+    days: BTreeMap<String, DayAnswers>,
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct DayAnswers {
+    #[serde(
+        default,
+        deserialize_with = "deserialize_answer",
+        skip_serializing_if = "Option::is_none"
+    )]
+    part_one: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_answer",
+        skip_serializing_if = "Option::is_none"
+    )]
+    part_two: Option<String>,
+}
+
+/// Answers are compared as text, but accept bare numbers as well as strings when hand written.
+fn deserialize_answer<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Answer {
+        Text(String),
+        Integer(i64),
+    }
+
+    Ok(Some(match Answer::deserialize(deserializer)? {
+        Answer::Text(answer) => answer,
+        Answer::Integer(answer) => answer.to_string(),
+    }))
+}
+
+impl Answers {
+    /// The answers file in the crate root.
+    pub fn default_path() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join(FILE_NAME)
+    }
+
+    /// Loads the answers, a missing file is treated as having no answers yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Answers::parse(&contents),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Answers::default()),
+            Err(err) => Err(io::Error::new(
+                err.kind(),
+                format!("unable to read {}: {}", path.display(), err),
+            )
+            .into()),
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        toml::from_str(contents).map_err(|err| {
+            let error = Error::parse(err.message().trim());
+            match err.span() {
+                Some(span) => error.at_offset(contents, span.start),
+                None => error,
+            }
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_toml()).map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!("unable to write {}: {}", path.display(), err),
+            )
+            .into()
+        })
+    }
+
+    pub fn to_toml(&self) -> String {
+        // Only strings and tables are written so serialising can't fail.
+        toml::to_string(self).unwrap_or_default()
+    }
+
+    pub fn get(&self, day: u8, part: Part) -> Option<&str> {
+        let day = self.days.get(&day_key(day))?;
+        match part {
+            Part::One => day.part_one.as_deref(),
+            Part::Two => day.part_two.as_deref(),
+        }
+    }
+
+    pub fn record(&mut self, day: u8, part: Part, answer: &str) {
+        let day = self.days.entry(day_key(day)).or_default();
+        let answer = Some(String::from(answer));
+        match part {
+            Part::One => day.part_one = answer,
+            Part::Two => day.part_two = answer,
+        }
+    }
+}
+
+fn day_key(day: u8) -> String {
+    format!("day_{:02}", day)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Status {
+    /// The answer matched the recorded answer.
+    Pass,
+    /// The answer differs from the recorded answer.
+    Fail,
+    /// There was no recorded answer so this one has been recorded.
+    Recorded,
+    /// There was no recorded answer and recording was turned off.
+    Unknown,
+    /// The part failed to produce an answer.
+    Error,
+}
+
+impl Status {
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Status::Fail | Status::Error)
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Pass => write!(f, "pass"),
+            Status::Fail => write!(f, "FAIL"),
+            Status::Recorded => write!(f, "recorded"),
+            Status::Unknown => write!(f, "unknown"),
+            Status::Error => write!(f, "ERROR"),
+        }
+    }
+}
+
+/// The outcome of verifying a single part, `actual` holds the error message when it failed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Check {
+    pub day: u8,
+    pub part: Part,
+    pub expected: Option<String>,
+    pub actual: String,
+    pub status: Status,
+}
+
+/// Compares a part's result with the recorded answer, recording it if there isn't one and
+/// `record` is set.
+pub fn check(
+    answers: &mut Answers,
+    day: u8,
+    part: Part,
+    result: &PartResult,
+    record: bool,
+) -> Check {
+    let expected = answers.get(day, part).map(String::from);
+    let (actual, status) = match (result, &expected) {
+        (Err(err), _) => (err.to_string(), Status::Error),
+        (Ok(actual), Some(expected)) if actual == expected => (actual.clone(), Status::Pass),
+        (Ok(actual), Some(_)) => (actual.clone(), Status::Fail),
+        (Ok(actual), None) if record => {
+            answers.record(day, part, actual);
+            (actual.clone(), Status::Recorded)
+        }
+        (Ok(actual), None) => (actual.clone(), Status::Unknown),
+    };
+
+    Check {
+        day,
+        part,
+        expected,
+        actual,
+        status,
+    }
+}
+
+/// A pass/fail table with one row per check.
+pub fn to_table(checks: &[Check]) -> String {
+    let mut table = format!(
+        "{:<4}{:<10}{:<20}{:<20}{}\n",
+        "day", "part", "expected", "actual", "result"
+    );
+    for check in checks {
+        table.push_str(&format!(
+            "{:<4}{:<10}{:<20}{:<20}{}\n",
+            format!("{:02}", check.day),
+            check.part.to_string(),
+            check.expected.as_deref().unwrap_or("-"),
+            check.actual,
+            check.status,
+        ));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, Answers, Status};
+    use crate::error::Error;
+    use crate::solution::Part;
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse(
+            r#"[day_01]
+part_one = "142"
+part_two = 281
+
+[day_04]
+part_two = "30"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(Some("142"), answers.get(1, Part::One));
+        assert_eq!(Some("281"), answers.get(1, Part::Two));
+        assert_eq!(None, answers.get(4, Part::One));
+        assert_eq!(Some("30"), answers.get(4, Part::Two));
+        assert_eq!(None, answers.get(2, Part::One));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        [
+            ("[day_01]\npart_three = \"1\"\n", "line 2, column 1"),
+            ("[day_01]\npart_one = \n", "line 2, column 12"),
+        ]
+        .iter()
+        .for_each(|(input, want)| {
+            let got = Answers::parse(input).unwrap_err().to_string();
+            assert!(got.starts_with(want), "want {}, got {}", want, got);
+        });
+    }
+
+    #[test]
+    fn test_record_round_trip() {
+        let mut answers = Answers::default();
+        answers.record(4, Part::Two, "30");
+        answers.record(1, Part::One, "142");
+
+        let toml = answers.to_toml();
+        assert_eq!(
+            "[day_01]\npart_one = \"142\"\n\n[day_04]\npart_two = \"30\"\n",
+            toml
+        );
+        assert_eq!(answers, Answers::parse(&toml).unwrap());
+    }
+
+    #[test]
+    fn test_check() {
+        let mut answers = Answers::parse("[day_02]\npart_one = \"8\"\n").unwrap();
+
+        let status = |answers: &mut Answers, part, result, record| {
+            check(answers, 2, part, &result, record).status
+        };
+
+        assert_eq!(
+            Status::Pass,
+            status(&mut answers, Part::One, Ok(String::from("8")), true)
+        );
+        assert_eq!(
+            Status::Fail,
+            status(&mut answers, Part::One, Ok(String::from("9")), true)
+        );
+        assert_eq!(
+            Status::Error,
+            status(&mut answers, Part::One, Err(Error::empty_input()), true)
+        );
+        assert_eq!(
+            Status::Unknown,
+            status(&mut answers, Part::Two, Ok(String::from("2286")), false)
+        );
+        assert_eq!(None, answers.get(2, Part::Two));
+        assert_eq!(
+            Status::Recorded,
+            status(&mut answers, Part::Two, Ok(String::from("2286")), true)
+        );
+        assert_eq!(Some("2286"), answers.get(2, Part::Two));
+    }
+}
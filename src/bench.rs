@@ -0,0 +1,181 @@
+//! Timing of each day's parse and parts, see [`bench`].
+
+use crate::error::Result;
+use crate::solution::{Part, Runner};
+use std::fmt;
+use std::time::Duration;
+
+/// A step of a day that is timed on its own.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Stage {
+    Parse,
+    Part(Part),
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::Parse => write!(f, "parse"),
+            Stage::Part(part) => write!(f, "{}", part),
+        }
+    }
+}
+
+/// The wall time of a stage over every iteration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+    pub day: u8,
+    pub stage: Stage,
+    pub iterations: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    /// Input bytes processed per second at the median time.
+    pub throughput: f64,
+}
+
+/// Times the parse and the selected parts of a day `iterations` times after one warm up run.
+pub fn bench(
+    runner: &dyn Runner,
+    input: &str,
+    parts: &[Part],
+    iterations: usize,
+) -> Result<Vec<Stats>> {
+    runner.time(input, parts)?;
+
+    let mut samples: Vec<(Stage, Vec<Duration>)> = vec![];
+    for _ in 0..iterations {
+        for (i, (stage, elapsed)) in runner.time(input, parts)?.into_iter().enumerate() {
+            if i == samples.len() {
+                samples.push((stage, vec![]));
+            }
+            samples[i].1.push(elapsed);
+        }
+    }
+
+    Ok(samples
+        .into_iter()
+        .map(|(stage, mut durations)| {
+            let (min, median, p95) = summarise(&mut durations);
+            Stats {
+                day: runner.day(),
+                stage,
+                iterations,
+                min,
+                median,
+                p95,
+                throughput: throughput(input.len(), median),
+            }
+        })
+        .collect())
+}
+
+/// The minimum, median and 95th percentile, using the nearest rank for percentiles.
+fn summarise(durations: &mut [Duration]) -> (Duration, Duration, Duration) {
+    if durations.is_empty() {
+        return (Duration::ZERO, Duration::ZERO, Duration::ZERO);
+    }
+
+    durations.sort_unstable();
+    let rank = |percentile: usize| {
+        let rank = (percentile * durations.len()).div_ceil(100);
+        durations[rank.max(1) - 1]
+    };
+
+    (durations[0], rank(50), rank(95))
+}
+
+fn throughput(bytes: usize, elapsed: Duration) -> f64 {
+    if elapsed.is_zero() {
+        return 0.0;
+    }
+    bytes as f64 / elapsed.as_secs_f64()
+}
+
+/// A human readable table of the stats, one row per stage.
+pub fn to_table(stats: &[Stats]) -> String {
+    let mut table = format!(
+        "{:<4}{:<10}{:>11}{:>12}{:>12}{:>12}{:>17}\n",
+        "day", "stage", "iterations", "min", "median", "p95", "throughput"
+    );
+    for stat in stats {
+        table.push_str(&format!(
+            "{:<4}{:<10}{:>11}{:>12}{:>12}{:>12}{:>12.2} MB/s\n",
+            format!("{:02}", stat.day),
+            stat.stage.to_string(),
+            stat.iterations,
+            format!("{:.2?}", stat.min),
+            format!("{:.2?}", stat.median),
+            format!("{:.2?}", stat.p95),
+            stat.throughput / 1_000_000.0,
+        ));
+    }
+    table
+}
+
+/// A CSV summary with times in nanoseconds, stable enough to diff between commits.
+pub fn to_csv(stats: &[Stats]) -> String {
+    let mut csv = String::from("day,stage,iterations,min_ns,median_ns,p95_ns,bytes_per_sec\n");
+    for stat in stats {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{:.0}\n",
+            stat.day,
+            stat.stage,
+            stat.iterations,
+            stat.min.as_nanos(),
+            stat.median.as_nanos(),
+            stat.p95.as_nanos(),
+            stat.throughput,
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bench, summarise, to_csv, Stage};
+    use crate::solution::{registry, Part};
+    use std::time::Duration;
+
+    #[test]
+    fn test_summarise() {
+        let ms = Duration::from_millis;
+
+        [
+            (vec![], (ms(0), ms(0), ms(0))),
+            (vec![7], (ms(7), ms(7), ms(7))),
+            (vec![3, 1, 2], (ms(1), ms(2), ms(3))),
+            (vec![4, 3, 2, 1], (ms(1), ms(2), ms(4))),
+            ((1..=100).rev().collect(), (ms(1), ms(50), ms(95))),
+        ]
+        .into_iter()
+        .for_each(|(input, want)| {
+            let mut durations: Vec<Duration> = input.into_iter().map(ms).collect();
+            assert_eq!(want, summarise(&mut durations));
+        });
+    }
+
+    #[test]
+    fn test_bench() {
+        let runner = registry()
+            .into_iter()
+            .find(|runner| runner.day() == 4)
+            .unwrap();
+
+        let stats = bench(runner.as_ref(), runner.example(), &[Part::Two], 3).unwrap();
+        let stages: Vec<Stage> = stats.iter().map(|stat| stat.stage).collect();
+        assert_eq!(vec![Stage::Parse, Stage::Part(Part::Two)], stages);
+        assert!(stats
+            .iter()
+            .all(|stat| stat.iterations == 3 && stat.min <= stat.p95));
+
+        let csv = to_csv(&stats);
+        let mut lines = csv.lines();
+        assert_eq!(
+            Some("day,stage,iterations,min_ns,median_ns,p95_ns,bytes_per_sec"),
+            lines.next()
+        );
+        assert!(lines.next().unwrap().starts_with("4,parse,3,"));
+        assert!(lines.next().unwrap().starts_with("4,part two,3,"));
+    }
+}
@@ -0,0 +1,1023 @@
+use aoc23::day_01::vocabulary::Vocabulary;
+use aoc23::day_01::NoDigit;
+use aoc23::day_02::{self, generate};
+use aoc23::input::InputSource;
+use aoc23::report::Format;
+use aoc23::solution::{ExplainFormat, Options, Part};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+pub const USAGE: &str = "usage: aoc23 [run] [options] [--output <format>]
+       aoc23 bench [options] [--iterations <n>] [--summary <path>]
+       aoc23 verify [options] [--answers <path>] [--no-record]
+       aoc23 explain --day <day> [options] [--output <format>]
+       aoc23 infer --day 2 [options] [--games <ids>] [--output <format>]
+       aoc23 query --day 2 [options] [--output <format>] <expression>
+       aoc23 format --day 2 [options] [--from <format>] [--output <format>]
+       aoc23 generate --day 2 [--games <n>] [--draws <n>] [--colours <n>] [--max-count <n>] [--seed <n>]
+
+commands:
+  run       run the selected days and parts (default)
+  bench     time the parse and each part of the selected days
+  verify    check the answers of the selected days against the recorded answers
+  explain   trace how each record of a day's input contributes to the answers
+  infer     estimate the smallest and the most likely bag behind day 2's games
+  query     list the day 2 games matching an expression such as 'max(red) > 10 and power > 500'
+  format    rewrite day 2's games in a canonical form, or convert them to and from JSON
+  generate  make up a day 2 input of any size, printing its bag and answers to stderr
+  help      print this message
+
+options:
+  --all                    select every available day (default)
+  --day <days>             a day, list or inclusive range, e.g. 3, 1,3 or 1..4
+  --part <parts>           a part, list or inclusive range, e.g. 2 or 1..2
+  --input <path>           read the input for a single day from a file, or stdin when the path is -
+  --example                use the example from each day's puzzle description
+  --big-integers           count with arbitrary precision from the start instead of only on overflow
+  --bag <cubes>            the cubes in day 2's bag, e.g. '12 red, 13 green, 14 blue' (the default)
+  --bag-file <path>        read day 2's bag from a TOML file with a count for each colour
+  --no-digit <what>        error (default) on a day 1 line without a digit, or skip it
+  --vocabulary <names>     day 1's words for digits, english, french, german, spanish or a list
+  --vocabulary-file <path> read day 1's words from a TOML file with the digit of each word
+  --word <word=digit>      add a word to day 1's words, e.g. 'half a dozen=6'
+  --ignore-case            ignore the case of letters in day 1's words
+  --unicode-digits         read the decimal digits of every script on day 1, e.g. ٣ or ３
+
+run options:
+  --output <format>  text (default), json, csv or junit
+  --stream           read the input a record at a time instead of all at once, day 2 only
+
+bench options:
+  --iterations <n>  number of timed runs of each day, 100 by default
+  --summary <path>  also write the results as CSV, or to stdout when the path is -
+
+verify options:
+  --answers <path>  the recorded answers, answers.toml in the crate root by default
+  --no-record       don't record answers for parts that have no recorded answer yet, never
+                    recorded with --input, --example or options that change the answers
+
+explain options:
+  --output <format>  text (default) or json
+
+infer options:
+  --games <ids>      only use these games, a list or inclusive range like --day
+  --output <format>  text (default) or json
+
+query options:
+  --output <format>  text (default) or json
+  <expression>       comparisons of numbers, id, draws, power, a colour or min, max or sum of a
+                     colour over the draws, joined with and, or, not and parentheses
+
+format options:
+  --from <format>    the format of the input, text (default) or json
+  --output <format>  text (default) or json
+
+generate options:
+  --games <n>      number of games, 100 by default
+  --draws <n>      the most draws in a game, 6 by default
+  --colours <n>    number of colours in the bag, from 1 to 8, 3 by default
+  --max-count <n>  the most cubes of a colour in the bag or a draw, 20 by default
+  --seed <n>       the same seed always makes the same input, 0 by default
+
+By default input is read from $AOC_INPUT_DIR/input_day_NN.txt, or data/input_day_NN.txt in the
+crate root when it isn't set. A relative $AOC_INPUT_DIR is taken from the crate root.";
+
+pub const DEFAULT_ITERATIONS: usize = 100;
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run(Run),
+    Bench(Bench),
+    Verify(Verify),
+    Explain(Explain),
+    Infer(Infer),
+    Query(Query),
+    Reformat(Reformat),
+    Generate(Generate),
+    Help,
+}
+
+/// The days and parts requested on the command line, `None` for days means every available day.
+#[derive(Debug, PartialEq)]
+pub struct Selection {
+    pub days: Option<Vec<u8>>,
+    pub parts: Vec<Part>,
+    pub input: Input,
+    pub options: Options,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Input {
+    /// Resolved from the environment, see [`InputSource::from_env`].
+    Default,
+    Example,
+    Source(InputSource),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Run {
+    pub selection: Selection,
+    pub output: Format,
+    /// Read the input as each day goes rather than all at once.
+    pub stream: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Bench {
+    pub selection: Selection,
+    pub iterations: usize,
+    pub summary: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Verify {
+    pub selection: Selection,
+    /// `None` for the answers file in the crate root.
+    pub answers: Option<PathBuf>,
+    pub record: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Explain {
+    /// Always a single day.
+    pub selection: Selection,
+    pub output: ExplainFormat,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Infer {
+    /// Always a single day.
+    pub selection: Selection,
+    /// `None` for every game.
+    pub games: Option<Vec<RangeInclusive<u32>>>,
+    pub output: ExplainFormat,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Query {
+    /// Always a single day.
+    pub selection: Selection,
+    pub expression: String,
+    pub output: ExplainFormat,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Reformat {
+    /// Always a single day.
+    pub selection: Selection,
+    pub from: ExplainFormat,
+    pub output: ExplainFormat,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Generate {
+    pub config: generate::Config,
+}
+
+/// The options shared by every command, collected before being checked as a whole.
+#[derive(Default)]
+struct SelectionArgs {
+    days: Option<Vec<u8>>,
+    parts: Option<Vec<Part>>,
+    all: bool,
+    input: Option<Input>,
+    options: Options,
+    /// Words added to day 1's vocabulary, or to the puzzle's when no vocabulary is chosen.
+    words: Vec<(String, i64)>,
+}
+
+impl SelectionArgs {
+    /// Handles `arg` if it is a selection option, returning whether it was.
+    fn parse(&mut self, arg: &str, args: &mut dyn Iterator<Item = String>) -> Result<bool, String> {
+        match arg {
+            "--all" => self.all = true,
+            "--day" | "-d" => {
+                self.days = Some(parse_numbers(&next_value(args, "--day")?, "day")?);
+            }
+            "--part" | "-p" => {
+                let mut selected: Vec<Part> = vec![];
+                for number in parse_numbers(&next_value(args, "--part")?, "part")? {
+                    selected.push(
+                        Part::from_number(number)
+                            .ok_or(format!("unknown part {}, expected 1 or 2", number))?,
+                    );
+                }
+                self.parts = Some(selected);
+            }
+            "--input" | "-i" => {
+                self.input = Some(match next_value(args, "--input")?.as_str() {
+                    "-" => Input::Source(InputSource::Stdin),
+                    path => Input::Source(InputSource::File(PathBuf::from(path))),
+                });
+            }
+            "--example" => self.input = Some(Input::Example),
+            "--big-integers" => self.options.big_integers = true,
+            "--bag" => {
+                let cubes = next_value(args, "--bag")?;
+                self.options.bag = Some(
+                    day_02::parse_bag(&cubes)
+                        .map_err(|err| format!("invalid bag '{}': {}", cubes, err))?,
+                );
+            }
+            "--bag-file" => {
+                let path = PathBuf::from(next_value(args, "--bag-file")?);
+                self.options.bag = Some(
+                    day_02::load_bag(&path)
+                        .map_err(|err| format!("{}: {}", path.display(), err))?,
+                );
+            }
+            "--vocabulary" => {
+                let names = next_value(args, "--vocabulary")?;
+                let vocabulary = Vocabulary::parse_names(&names).map_err(|err| err.to_string())?;
+                self.vocabulary().extend(&vocabulary);
+            }
+            "--vocabulary-file" => {
+                let path = PathBuf::from(next_value(args, "--vocabulary-file")?);
+                let vocabulary = Vocabulary::load(&path)
+                    .map_err(|err| format!("{}: {}", path.display(), err))?;
+                self.vocabulary().extend(&vocabulary);
+            }
+            "--word" => {
+                let value = next_value(args, "--word")?;
+                let word = value
+                    .rsplit_once('=')
+                    .and_then(|(word, digit)| Some((word, digit.trim().parse::<i64>().ok()?)))
+                    .ok_or(format!("invalid word '{}', expected <word>=<digit>", value))?;
+                self.words.push((String::from(word.0), word.1));
+            }
+            "--ignore-case" => self.options.ignore_case = true,
+            "--unicode-digits" => self.options.unicode_digits = true,
+            "--no-digit" => {
+                self.options.no_digit = next_value(args, "--no-digit")?.parse::<NoDigit>()?;
+            }
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    /// The vocabulary chosen so far, empty before the first one.
+    fn vocabulary(&mut self) -> &mut Vocabulary {
+        self.options.vocabulary.get_or_insert_with(Vocabulary::new)
+    }
+
+    fn build(mut self) -> Result<Selection, String> {
+        if self.all && self.days.is_some() {
+            return Err(String::from("--all and --day cannot be used together"));
+        }
+
+        // A single input can't hold more than one day's puzzle.
+        if let Some(Input::Source(_)) = self.input {
+            if !matches!(self.days.as_deref(), Some([_])) {
+                return Err(String::from("--input requires a single --day"));
+            }
+        }
+
+        if !self.words.is_empty() {
+            let vocabulary = self
+                .options
+                .vocabulary
+                .get_or_insert_with(Vocabulary::puzzle);
+            for (word, digit) in &self.words {
+                vocabulary
+                    .add(word, *digit)
+                    .map_err(|err| format!("invalid word '{}={}': {}", word, digit, err))?;
+            }
+        }
+
+        Ok(Selection {
+            days: self.days,
+            parts: self.parts.unwrap_or_else(|| Part::ALL.to_vec()),
+            input: self.input.unwrap_or(Input::Default),
+            options: self.options,
+        })
+    }
+}
+
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Command, String> {
+    let mut args = args.into_iter().peekable();
+
+    // The subcommand is optional, anything starting with a dash is treated as an option to `run`.
+    let command = match args.peek().map(String::as_str) {
+        None => String::from("run"),
+        Some(arg) if arg.starts_with('-') => String::from("run"),
+        Some(_) => args.next().unwrap_or_default(),
+    };
+    match command.as_str() {
+        "run" | "bench" | "verify" | "explain" | "infer" | "query" | "format" | "generate" => {}
+        "help" => return Ok(Command::Help),
+        _ => return Err(format!("unknown command '{}'", command)),
+    }
+
+    let mut selection = SelectionArgs::default();
+    let mut iterations = DEFAULT_ITERATIONS;
+    let mut summary: Option<PathBuf> = None;
+    let mut answers: Option<PathBuf> = None;
+    let mut record = true;
+    let mut output = Format::default();
+    let mut explain_output = ExplainFormat::default();
+    let mut stream = false;
+    let mut games: Option<Vec<RangeInclusive<u32>>> = None;
+    let mut expression: Option<String> = None;
+    let mut from = ExplainFormat::default();
+    let mut config = generate::Config::default();
+
+    while let Some(arg) = args.next() {
+        if selection.parse(&arg, &mut args)? {
+            continue;
+        }
+
+        match (command.as_str(), arg.as_str()) {
+            ("run", "--output" | "-o") => {
+                output = next_value(&mut args, "--output")?.parse::<Format>()?;
+            }
+            ("run", "--stream") => stream = true,
+            ("infer", "--games" | "-g") => {
+                games = Some(parse_ranges(&next_value(&mut args, "--games")?, "game")?);
+            }
+            ("generate", "--games") => config.games = parse_value(&mut args, "--games")?,
+            ("generate", "--draws") => config.draws = parse_value(&mut args, "--draws")?,
+            ("generate", "--colours") => config.colours = parse_value(&mut args, "--colours")?,
+            ("generate", "--max-count") => {
+                config.max_count = parse_value(&mut args, "--max-count")?;
+            }
+            ("generate", "--seed") => config.seed = parse_value(&mut args, "--seed")?,
+            ("format", "--from") => from = parse_format(&mut args, &command, "--from")?,
+            ("explain" | "infer" | "query" | "format", "--output" | "-o") => {
+                explain_output = parse_format(&mut args, &command, "--output")?;
+            }
+            ("bench", "--iterations" | "-n") => {
+                let value = next_value(&mut args, "--iterations")?;
+                iterations = match value.parse::<usize>() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(format!("invalid iterations '{}'", value)),
+                };
+            }
+            ("bench", "--summary") => {
+                summary = Some(PathBuf::from(next_value(&mut args, "--summary")?));
+            }
+            ("verify", "--answers") => {
+                answers = Some(PathBuf::from(next_value(&mut args, "--answers")?));
+            }
+            ("verify", "--no-record") => record = false,
+            (_, "--help" | "-h") => return Ok(Command::Help),
+            ("query", _) if !arg.starts_with('-') && expression.is_none() => {
+                expression = Some(arg);
+            }
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
+
+    let selection = selection.build()?;
+    match command.as_str() {
+        "bench" => Ok(Command::Bench(Bench {
+            selection,
+            iterations,
+            summary,
+        })),
+        "verify" => {
+            // Recorded answers are the answers to the real inputs, anything else would record
+            // answers to a different question and then hold every later run to them.
+            let record = record
+                && selection.input == Input::Default
+                && selection.options == Options::default();
+            Ok(Command::Verify(Verify {
+                selection,
+                answers,
+                record,
+            }))
+        }
+        "explain" => {
+            // An explanation is a record by record account, several days of it wouldn't be read.
+            if !matches!(selection.days.as_deref(), Some([_])) {
+                return Err(String::from("explain requires a single --day"));
+            }
+            Ok(Command::Explain(Explain {
+                selection,
+                output: explain_output,
+            }))
+        }
+        "infer" | "query" | "format" | "generate" if selection.days.as_deref() != Some(&[2]) => {
+            Err(format!("{} requires --day 2", command))
+        }
+        // Only day 2 can be scored a record at a time, every other day would fail.
+        "run" if stream && selection.days.as_deref() != Some(&[2]) => {
+            Err(String::from("--stream requires --day 2"))
+        }
+        "query" => Ok(Command::Query(Query {
+            selection,
+            expression: expression.ok_or("missing the query expression")?,
+            output: explain_output,
+        })),
+        "generate" => Ok(Command::Generate(Generate { config })),
+        "format" => Ok(Command::Reformat(Reformat {
+            selection,
+            from,
+            output: explain_output,
+        })),
+        "infer" => Ok(Command::Infer(Infer {
+            selection,
+            games,
+            output: explain_output,
+        })),
+        _ => Ok(Command::Run(Run {
+            selection,
+            output,
+            stream,
+        })),
+    }
+}
+
+fn next_value(args: &mut dyn Iterator<Item = String>, name: &str) -> Result<String, String> {
+    args.next().ok_or(format!("missing value for {}", name))
+}
+
+fn parse_value<T: FromStr>(
+    args: &mut dyn Iterator<Item = String>,
+    name: &str,
+) -> Result<T, String> {
+    let value = next_value(args, name)?;
+    value
+        .parse::<T>()
+        .map_err(|_| format!("invalid value '{}' for {}", value, name))
+}
+
+/// Parses the text or JSON format of `command`'s `name` option, naming both when it is neither.
+fn parse_format(
+    args: &mut dyn Iterator<Item = String>,
+    command: &str,
+    name: &str,
+) -> Result<ExplainFormat, String> {
+    let value = next_value(args, name)?;
+    value.parse::<ExplainFormat>().map_err(|_| {
+        format!(
+            "unknown format '{}' for {} {}, expected text or json",
+            value, command, name
+        )
+    })
+}
+
+/// Parses a single number, a comma separated list or an inclusive range such as `1..4` or `1..=4`.
+fn parse_numbers<T>(value: &str, name: &str) -> Result<Vec<T>, String>
+where
+    T: FromStr + Ord + Copy,
+    RangeInclusive<T>: Iterator<Item = T>,
+{
+    let mut numbers: Vec<T> = parse_ranges(value, name)?.into_iter().flatten().collect();
+
+    numbers.sort_unstable();
+    numbers.dedup();
+
+    Ok(numbers)
+}
+
+/// Like [`parse_numbers`] but keeping each range as it is, for numbers too wide to list one by one.
+fn parse_ranges<T>(value: &str, name: &str) -> Result<Vec<RangeInclusive<T>>, String>
+where
+    T: FromStr + Ord + Copy,
+{
+    let mut ranges: Vec<RangeInclusive<T>> = vec![];
+
+    for item in value.split(',') {
+        let parse = |n: &str| {
+            n.trim()
+                .parse::<T>()
+                .map_err(|_| format!("invalid {} '{}'", name, n.trim()))
+        };
+
+        if let Some((start, end)) = item.split_once("..") {
+            let (start, end) = (parse(start)?, parse(end.trim_start_matches('='))?);
+            if start > end {
+                return Err(format!("invalid {} range '{}'", name, item));
+            }
+            ranges.push(start..=end);
+        } else {
+            let number = parse(item)?;
+            ranges.push(number..=number);
+        }
+    }
+
+    Ok(ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_args, Bench, Command, Explain, Generate, Infer, Input, Query, Reformat, Run,
+        Selection, Verify, DEFAULT_ITERATIONS,
+    };
+    use aoc23::day_01::vocabulary::Vocabulary;
+    use aoc23::day_01::NoDigit;
+    use aoc23::day_02::generate;
+    use aoc23::input::InputSource;
+    use aoc23::report::Format;
+    use aoc23::solution::{ExplainFormat, Options, Part};
+    use std::path::PathBuf;
+
+    fn test_parse_args_case(input: &str, want: Result<Command, String>) -> Result<(), String> {
+        let got = parse_args(input.split_whitespace().map(String::from));
+        if got != want {
+            Err(format!("{}: want {:?}, got {:?}", input, want, got))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_parse_args_bag() {
+        let args = ["run", "--day", "2", "--bag", "3 blue, 2 purple"];
+
+        match parse_args(args.iter().map(|arg| String::from(*arg))) {
+            Ok(Command::Run(run)) => assert_eq!(
+                Some([("blue", 3), ("purple", 2)].into_iter().collect()),
+                run.selection.options.bag
+            ),
+            got => panic!("want a run with a bag, got {:?}", got),
+        }
+    }
+
+    #[test]
+    fn test_parse_args_vocabulary() {
+        let parse = |args: &[&str]| match parse_args(args.iter().map(|arg| String::from(*arg))) {
+            Ok(Command::Run(run)) => Ok(run.selection.options),
+            Ok(command) => Err(format!("want run, got {:?}", command)),
+            Err(err) => Err(err),
+        };
+
+        let mut want = Vocabulary::puzzle();
+        want.add("half a dozen", 6).unwrap();
+        assert_eq!(
+            Ok(Options {
+                vocabulary: Some(want),
+                ignore_case: true,
+                ..Options::default()
+            }),
+            parse(&["--word", "half a dozen=6", "--ignore-case"])
+        );
+        assert_eq!(
+            Ok(Options {
+                unicode_digits: true,
+                ..Options::default()
+            }),
+            parse(&["--unicode-digits"])
+        );
+
+        let mut want = Vocabulary::parse_names("french,german").unwrap();
+        want.add("one", 1).unwrap();
+        assert_eq!(
+            Ok(Options {
+                vocabulary: Some(want),
+                ..Options::default()
+            }),
+            parse(&[
+                "--word",
+                "one=1",
+                "--vocabulary",
+                "french",
+                "--vocabulary",
+                "german"
+            ])
+        );
+
+        [
+            (
+                vec!["--vocabulary", "french,dutch"],
+                "unsupported: unknown vocabulary 'dutch', expected one of english, french, german, spanish",
+            ),
+            (
+                vec!["--word", "dozen"],
+                "invalid word 'dozen', expected <word>=<digit>",
+            ),
+            (
+                vec!["--word", "dozen=12"],
+                "invalid word 'dozen=12': malformed record: 'dozen' stands for 12, words can only stand for a digit from 0 to 9",
+            ),
+        ]
+        .iter()
+        .for_each(|(args, want)| assert_eq!(Err(String::from(*want)), parse(args)));
+    }
+
+    #[test]
+    fn test_parse_args_query() {
+        let parse = |args: &[&str]| parse_args(args.iter().map(|arg| String::from(*arg)));
+
+        assert_eq!(
+            Ok(Command::Query(Query {
+                selection: Selection {
+                    days: Some(vec![2]),
+                    parts: vec![Part::One, Part::Two],
+                    input: Input::Example,
+                    options: Options::default(),
+                },
+                expression: String::from("max(red) > 10 or power > 500"),
+                output: ExplainFormat::Json,
+            })),
+            parse(&[
+                "query",
+                "--day",
+                "2",
+                "--example",
+                "max(red) > 10 or power > 500",
+                "-o",
+                "json"
+            ])
+        );
+        assert_eq!(
+            Err(String::from("missing the query expression")),
+            parse(&["query", "--day", "2"])
+        );
+        assert_eq!(
+            Err(String::from("unknown argument 'red > 2'")),
+            parse(&["query", "--day", "2", "red > 1", "red > 2"])
+        );
+        assert_eq!(
+            Err(String::from("query requires --day 2")),
+            parse(&["query", "red > 1"])
+        );
+    }
+
+    #[test]
+    fn test_parse_args() -> Result<(), String> {
+        let run = |days: Option<Vec<u8>>, parts: Vec<Part>| {
+            Ok(Command::Run(Run {
+                selection: Selection {
+                    days,
+                    parts,
+                    input: Input::Default,
+                    options: Options::default(),
+                },
+                output: Format::Text,
+                stream: false,
+            }))
+        };
+        let run_with = |days: Vec<u8>, input: Input| {
+            Ok(Command::Run(Run {
+                selection: Selection {
+                    days: Some(days),
+                    parts: vec![Part::One, Part::Two],
+                    input,
+                    options: Options::default(),
+                },
+                output: Format::Text,
+                stream: false,
+            }))
+        };
+
+        [
+            ("", run(None, vec![Part::One, Part::Two])),
+            ("run", run(None, vec![Part::One, Part::Two])),
+            ("run --all", run(None, vec![Part::One, Part::Two])),
+            ("--day 3", run(Some(vec![3]), vec![Part::One, Part::Two])),
+            ("run --day 3 --part 2", run(Some(vec![3]), vec![Part::Two])),
+            (
+                "run --day 1..4",
+                run(Some(vec![1, 2, 3, 4]), vec![Part::One, Part::Two]),
+            ),
+            (
+                "run --day 1..=2",
+                run(Some(vec![1, 2]), vec![Part::One, Part::Two]),
+            ),
+            (
+                "run --day 4,1,1 --part 1..2",
+                run(Some(vec![1, 4]), vec![Part::One, Part::Two]),
+            ),
+            (
+                "run --day 2 --input data/day_02.txt",
+                run_with(
+                    vec![2],
+                    Input::Source(InputSource::File(PathBuf::from("data/day_02.txt"))),
+                ),
+            ),
+            (
+                "run --day 2 --input -",
+                run_with(vec![2], Input::Source(InputSource::Stdin)),
+            ),
+            (
+                "run --day 1,2 --example",
+                run_with(vec![1, 2], Input::Example),
+            ),
+            (
+                "run --input -",
+                Err(String::from("--input requires a single --day")),
+            ),
+            (
+                "run --day 1..2 --input -",
+                Err(String::from("--input requires a single --day")),
+            ),
+            (
+                "bench",
+                Ok(Command::Bench(Bench {
+                    selection: Selection {
+                        days: None,
+                        parts: vec![Part::One, Part::Two],
+                        input: Input::Default,
+                        options: Options::default(),
+                    },
+                    iterations: DEFAULT_ITERATIONS,
+                    summary: None,
+                })),
+            ),
+            (
+                "bench --day 2 --part 1 -n 10 --summary bench.csv",
+                Ok(Command::Bench(Bench {
+                    selection: Selection {
+                        days: Some(vec![2]),
+                        parts: vec![Part::One],
+                        input: Input::Default,
+                        options: Options::default(),
+                    },
+                    iterations: 10,
+                    summary: Some(PathBuf::from("bench.csv")),
+                })),
+            ),
+            ("bench -n 0", Err(String::from("invalid iterations '0'"))),
+            (
+                "run --iterations 10",
+                Err(String::from("unknown argument '--iterations'")),
+            ),
+            (
+                "verify --day 1 --answers mine.toml --no-record",
+                Ok(Command::Verify(Verify {
+                    selection: Selection {
+                        days: Some(vec![1]),
+                        parts: vec![Part::One, Part::Two],
+                        input: Input::Default,
+                        options: Options::default(),
+                    },
+                    answers: Some(PathBuf::from("mine.toml")),
+                    record: false,
+                })),
+            ),
+            (
+                "verify --day 3",
+                Ok(Command::Verify(Verify {
+                    selection: Selection {
+                        days: Some(vec![3]),
+                        parts: vec![Part::One, Part::Two],
+                        input: Input::Default,
+                        options: Options::default(),
+                    },
+                    answers: None,
+                    record: true,
+                })),
+            ),
+            (
+                "verify --day 2 --example --big-integers",
+                Ok(Command::Verify(Verify {
+                    selection: Selection {
+                        days: Some(vec![2]),
+                        parts: vec![Part::One, Part::Two],
+                        input: Input::Example,
+                        options: Options {
+                            big_integers: true,
+                            ..Options::default()
+                        },
+                    },
+                    answers: None,
+                    record: false,
+                })),
+            ),
+            (
+                "verify --day 1 --no-digit skip",
+                Ok(Command::Verify(Verify {
+                    selection: Selection {
+                        days: Some(vec![1]),
+                        parts: vec![Part::One, Part::Two],
+                        input: Input::Default,
+                        options: Options {
+                            no_digit: NoDigit::Skip,
+                            ..Options::default()
+                        },
+                    },
+                    answers: None,
+                    record: false,
+                })),
+            ),
+            (
+                "run --no-record",
+                Err(String::from("unknown argument '--no-record'")),
+            ),
+            (
+                "run --day 3 --output junit",
+                Ok(Command::Run(Run {
+                    selection: Selection {
+                        days: Some(vec![3]),
+                        parts: vec![Part::One, Part::Two],
+                        input: Input::Default,
+                        options: Options::default(),
+                    },
+                    output: Format::Junit,
+                    stream: false,
+                })),
+            ),
+            (
+                "run --output xml",
+                Err(String::from(
+                    "unknown output format 'xml', expected text, json, csv or junit",
+                )),
+            ),
+            (
+                "bench --output json",
+                Err(String::from("unknown argument '--output'")),
+            ),
+            (
+                "run --day 4 --big-integers",
+                Ok(Command::Run(Run {
+                    selection: Selection {
+                        days: Some(vec![4]),
+                        parts: vec![Part::One, Part::Two],
+                        input: Input::Default,
+                        options: Options {
+                            big_integers: true,
+                            ..Options::default()
+                        },
+                    },
+                    output: Format::Text,
+                    stream: false,
+                })),
+            ),
+            (
+                "explain --day 4 --example --output json",
+                Ok(Command::Explain(Explain {
+                    selection: Selection {
+                        days: Some(vec![4]),
+                        parts: vec![Part::One, Part::Two],
+                        input: Input::Example,
+                        options: Options::default(),
+                    },
+                    output: ExplainFormat::Json,
+                })),
+            ),
+            (
+                "explain",
+                Err(String::from("explain requires a single --day")),
+            ),
+            (
+                "infer --day 2 --games 1..3,10 --output json",
+                Ok(Command::Infer(Infer {
+                    selection: Selection {
+                        days: Some(vec![2]),
+                        parts: vec![Part::One, Part::Two],
+                        input: Input::Default,
+                        options: Options::default(),
+                    },
+                    games: Some(vec![1..=3, 10..=10]),
+                    output: ExplainFormat::Json,
+                })),
+            ),
+            (
+                // The widest range is kept as it is rather than listing every id in it.
+                "infer --day 2 --games 1..4294967295",
+                Ok(Command::Infer(Infer {
+                    selection: Selection {
+                        days: Some(vec![2]),
+                        parts: vec![Part::One, Part::Two],
+                        input: Input::Default,
+                        options: Options::default(),
+                    },
+                    games: Some(vec![1..=u32::MAX]),
+                    output: ExplainFormat::Text,
+                })),
+            ),
+            ("infer --day 4", Err(String::from("infer requires --day 2"))),
+            (
+                "infer --day 2 --games 1,x",
+                Err(String::from("invalid game 'x'")),
+            ),
+            (
+                "run --games 1",
+                Err(String::from("unknown argument '--games'")),
+            ),
+            (
+                "explain --day 4 --output csv",
+                Err(String::from(
+                    "unknown format 'csv' for explain --output, expected text or json",
+                )),
+            ),
+            (
+                "run --day 2 --bag 1_red",
+                Err(String::from(
+                    "invalid bag '1_red': column 2: malformed record: expected a space after the cube count, found '_'",
+                )),
+            ),
+            (
+                "run --day 2 --stream",
+                Ok(Command::Run(Run {
+                    selection: Selection {
+                        days: Some(vec![2]),
+                        parts: vec![Part::One, Part::Two],
+                        input: Input::Default,
+                        options: Options::default(),
+                    },
+                    output: Format::Text,
+                    stream: true,
+                })),
+            ),
+            (
+                "format --day 2 --from json --example",
+                Ok(Command::Reformat(Reformat {
+                    selection: Selection {
+                        days: Some(vec![2]),
+                        parts: vec![Part::One, Part::Two],
+                        input: Input::Example,
+                        options: Options::default(),
+                    },
+                    from: ExplainFormat::Json,
+                    output: ExplainFormat::Text,
+                })),
+            ),
+            (
+                "generate --day 2 --games 1000 --colours 5 --seed 7",
+                Ok(Command::Generate(Generate {
+                    config: generate::Config {
+                        games: 1000,
+                        colours: 5,
+                        seed: 7,
+                        ..generate::Config::default()
+                    },
+                })),
+            ),
+            (
+                "generate --day 2 --draws many",
+                Err(String::from("invalid value 'many' for --draws")),
+            ),
+            (
+                "generate --games 10",
+                Err(String::from("generate requires --day 2")),
+            ),
+            (
+                "format --day 1",
+                Err(String::from("format requires --day 2")),
+            ),
+            (
+                "query --day 2 -o xml 'red > 1'",
+                Err(String::from(
+                    "unknown format 'xml' for query --output, expected text or json",
+                )),
+            ),
+            (
+                "format --day 2 --from csv",
+                Err(String::from(
+                    "unknown format 'csv' for format --from, expected text or json",
+                )),
+            ),
+            (
+                "run --day 1 --no-digit skip",
+                Ok(Command::Run(Run {
+                    selection: Selection {
+                        days: Some(vec![1]),
+                        parts: vec![Part::One, Part::Two],
+                        input: Input::Default,
+                        options: Options {
+                            no_digit: NoDigit::Skip,
+                            ..Options::default()
+                        },
+                    },
+                    output: Format::Text,
+                    stream: false,
+                })),
+            ),
+            (
+                "run --no-digit zero",
+                Err(String::from(
+                    "unknown no digit policy 'zero', expected error or skip",
+                )),
+            ),
+            (
+                "verify --stream",
+                Err(String::from("unknown argument '--stream'")),
+            ),
+            ("--stream", Err(String::from("--stream requires --day 2"))),
+            (
+                "run --day 1,2 --stream",
+                Err(String::from("--stream requires --day 2")),
+            ),
+            ("help", Ok(Command::Help)),
+            ("run --help", Ok(Command::Help)),
+            (
+                "run --part 3",
+                Err(String::from("unknown part 3, expected 1 or 2")),
+            ),
+            ("run --day x", Err(String::from("invalid day 'x'"))),
+            (
+                "run --day 4..1",
+                Err(String::from("invalid day range '4..1'")),
+            ),
+            ("run --day", Err(String::from("missing value for --day"))),
+            (
+                "run --all --day 1",
+                Err(String::from("--all and --day cannot be used together")),
+            ),
+            (
+                "run --verbose",
+                Err(String::from("unknown argument '--verbose'")),
+            ),
+            ("walk", Err(String::from("unknown command 'walk'"))),
+        ]
+        .into_iter()
+        .try_for_each(|(input, want)| test_parse_args_case(input, want))?;
+
+        Ok(())
+    }
+}
@@ -1,137 +1,299 @@
-use std::collections::HashMap;
-use std::env;
-use std::error::Error;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::num::ParseIntError;
-
-pub fn part_one() -> Result<(), Box<dyn Error>> {
-    let file_path = format!(
-        "{}/data/input_day_01.txt",
-        env::current_dir().unwrap().display()
-    );
-    let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
-    let mut total: i32 = 0;
-
-    for line_result in reader.lines() {
-        match line_result {
-            Ok(line) => {
-                let line = line.as_str();
-                match extract_digits(line) {
-                    Ok(line_total) => total += line_total,
-                    Err(err) => eprintln!("error reading file: {}", err),
-                }
-            }
-            Err(err) => {
-                eprintln!("error reading line from file: {}", err);
-            }
+//! Day 1: Trebuchet?! Recover calibration values from the first and last digit on each line.
+
+pub mod matcher;
+pub mod vocabulary;
+
+use crate::error::{Error, Result};
+use crate::solution::{ExplainFormat, Options, Solution};
+use matcher::{Match, Matcher};
+use serde::Serialize;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use vocabulary::Vocabulary;
+
+/// What to do with a line that has no digit in it, which has no calibration value.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum NoDigit {
+    /// Fail with the number of the line.
+    #[default]
+    Error,
+    /// Count the line as zero.
+    Skip,
+}
+
+impl FromStr for NoDigit {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "error" => Ok(NoDigit::Error),
+            "skip" => Ok(NoDigit::Skip),
+            _ => Err(format!(
+                "unknown no digit policy '{}', expected error or skip",
+                value
+            )),
         }
     }
+}
 
-    println!("day 01: total - part one: {}", total);
-    Ok(())
+/// The calibration document along with what to do with lines that have no digit and the words
+/// part two reads as digits.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Calibration {
+    pub lines: Vec<String>,
+    pub no_digit: NoDigit,
+    /// Whether digits from every script count in part one, not just `0` to `9`.
+    pub unicode_digits: bool,
+    pub words: Matcher,
 }
 
-pub fn part_two() -> Result<(), Box<dyn Error>> {
-    let file_path = format!(
-        "{}/data/input_day_01.txt",
-        env::current_dir().unwrap().display()
-    );
-    let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
-    let mut total: i32 = 0;
-
-    for line_result in reader.lines() {
-        match line_result {
-            Ok(line) => {
-                let line = line.as_str();
-                match extract_digits_and_words(line) {
-                    Ok(line_total) => total += line_total,
-                    Err(err) => eprintln!("error reading file: {}", err),
-                }
-            }
-            Err(err) => {
-                eprintln!("error reading line from file: {}", err);
-            }
+impl Calibration {
+    /// The sum of `value` over every line, following the no digit policy.
+    fn total<F: Fn(&str) -> Option<i32>>(&self, value: F) -> Result<i32> {
+        let mut total: i32 = 0;
+        for (i, line) in self.lines.iter().enumerate() {
+            total += match (value(line), self.no_digit) {
+                (Some(value), _) => value,
+                (None, NoDigit::Skip) => 0,
+                (None, NoDigit::Error) => return Err(no_digit().at_line(i + 1)),
+            };
         }
+
+        Ok(total)
+    }
+}
+
+/// Part one uses only numeric digits, part two also accepts digits spelled out as words.
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: u8 = 1;
+    const EXAMPLE: &'static str = "1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet
+";
+
+    type Input = Calibration;
+    type Answer = i32;
+
+    fn parse(input: &str, options: &Options) -> Result<Self::Input> {
+        let words = match &options.vocabulary {
+            Some(vocabulary) => vocabulary.matcher(options.ignore_case, options.unicode_digits),
+            None => Vocabulary::puzzle().matcher(options.ignore_case, options.unicode_digits),
+        };
+        Ok(Calibration {
+            lines: input.lines().map(String::from).collect(),
+            no_digit: options.no_digit,
+            unicode_digits: options.unicode_digits,
+            words,
+        })
+    }
+
+    fn part_one(input: &Self::Input) -> Result<Self::Answer> {
+        input.total(|line| digits_value(line, input.unicode_digits))
     }
 
-    println!("day 01: total - part two: {}", total);
-    Ok(())
+    fn part_two(input: &Self::Input) -> Result<Self::Answer> {
+        input.total(|line| input.words.calibration_value(line))
+    }
+
+    fn explain(input: &Self::Input, format: ExplainFormat) -> Option<String> {
+        let traces = trace(input);
+        Some(match format {
+            ExplainFormat::Text => trace_table(&traces),
+            ExplainFormat::Json => trace_json(&traces),
+        })
+    }
+}
+
+fn no_digit() -> Error {
+    Error::malformed("no digit found")
+}
+
+/// The calibration value of a line made from its first and last numeric digit.
+pub fn extract_digits(line: &str) -> Result<i32> {
+    digits_value(line, false).ok_or_else(no_digit)
+}
+
+/// The calibration value of a line where digits may also be spelled out, such as `two1nine`, with
+/// the puzzle's words. [`Vocabulary::matcher`] reads other words.
+pub fn extract_digits_and_words(line: &str) -> Result<i32> {
+    puzzle_words().calibration_value(line).ok_or_else(no_digit)
 }
 
-fn extract_digits(line: &str) -> Result<i32, ParseIntError> {
-    let mut nums = Vec::new();
-    for c in line.chars() {
-        if let Some(digit) = c.to_digit(10) {
-            nums.push(digit);
-            continue;
+fn digits_value(line: &str, unicode_digits: bool) -> Option<i32> {
+    let digit = |c: char| {
+        if unicode_digits {
+            decimal_digit(c)
+        } else {
+            c.to_digit(10)
         }
-    }
+    };
+    let first = line.chars().find_map(digit)?;
+    let last = line.chars().rev().find_map(digit)?;
+
+    Some((first * 10 + last) as i32)
+}
 
-    format!(
-        "{}{}",
-        nums.first().unwrap().to_string(),
-        nums.last().unwrap().to_string()
-    )
-    .parse()
-}
-
-fn extract_digits_and_words(line: &str) -> Result<i32, ParseIntError> {
-    let mapper: HashMap<&str, i32> = [
-        ("0", 0),
-        ("1", 1),
-        ("2", 2),
-        ("3", 3),
-        ("4", 4),
-        ("5", 5),
-        ("6", 6),
-        ("7", 7),
-        ("8", 8),
-        ("9", 9),
-        ("one", 1),
-        ("two", 2),
-        ("three", 3),
-        ("four", 4),
-        ("five", 5),
-        ("six", 6),
-        ("seven", 7),
-        ("eight", 8),
-        ("nine", 9),
-    ]
-    .iter()
-    .cloned()
-    .collect();
-
-    let chars: Vec<char> = line.chars().collect();
-    let mut nums = Vec::new();
-    let mut partial = String::with_capacity(4);
-
-    // Build up each char to a partial and try to match this to the mapper.
-    for i in 0..line.len() {
-        partial.clear();
-        for ii in 0..(line.len() - i) {
-            // Add a char to the partial until we get a match or fall through.
-            partial.push(chars[i + ii]);
-            if let Some(&num_word) = mapper.get(partial.as_str()) {
-                nums.push(num_word);
-                break;
+/// A digit or word found on a line by part two.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Token {
+    pub text: String,
+    /// The column of its first character, counting from 1.
+    pub column: usize,
+    pub digit: i32,
+    /// Whether it shares characters with another token, such as the `two` and `one` in `twone`.
+    pub overlaps: bool,
+}
+
+/// How a single line came to its calibration values, `None` for a line without a digit.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LineTrace {
+    pub line: usize,
+    pub text: String,
+    pub part_one: Option<i32>,
+    /// Every token part two found, in order of column.
+    pub tokens: Vec<Token>,
+    pub first: Option<Token>,
+    pub last: Option<Token>,
+    pub part_two: Option<i32>,
+}
+
+/// Finds the calibration values of every line the same way as the parts, keeping every token part
+/// two found along the way.
+pub fn trace(input: &Calibration) -> Vec<LineTrace> {
+    input
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let matches = input.words.find_all(line);
+            let token = |found: &Match| Token {
+                text: String::from(&line[found.start..found.end]),
+                column: line[..found.start].chars().count() + 1,
+                digit: found.value,
+                overlaps: matches.iter().any(|other| {
+                    other != found && other.start < found.end && found.start < other.end
+                }),
+            };
+
+            LineTrace {
+                line: i + 1,
+                text: line.clone(),
+                part_one: digits_value(line, input.unicode_digits),
+                tokens: matches.iter().map(token).collect(),
+                first: input.words.first(line).as_ref().map(token),
+                last: input.words.last(line).as_ref().map(token),
+                part_two: input.words.calibration_value(line),
             }
-        }
+        })
+        .collect()
+}
+
+/// One row per line with both values and part two's tokens as `text@column`, marking those that
+/// overlap another token.
+pub fn trace_table(traces: &[LineTrace]) -> String {
+    let value = |value: Option<i32>| value.map_or(String::from("-"), |value| value.to_string());
+    let text = |token: &Option<Token>| {
+        token
+            .as_ref()
+            .map_or(String::from("-"), |token| token.text.clone())
+    };
+
+    let mut table = format!(
+        "{:<6}{:>6}  {:<10}{:<10}{:>6}  {}\n",
+        "line", "part 1", "first", "last", "part 2", "tokens"
+    );
+    for trace in traces {
+        let tokens: Vec<String> = trace
+            .tokens
+            .iter()
+            .map(|token| {
+                let overlaps = if token.overlaps { "*" } else { "" };
+                format!("{}@{}{}", token.text, token.column, overlaps)
+            })
+            .collect();
+        table.push_str(&format!(
+            "{:<6}{:>6}  {:<10}{:<10}{:>6}  {}\n",
+            trace.line,
+            value(trace.part_one),
+            text(&trace.first),
+            text(&trace.last),
+            value(trace.part_two),
+            if tokens.is_empty() {
+                String::from("-")
+            } else {
+                tokens.join(" ")
+            }
+        ));
     }
 
-    Ok(format!(
-        "{}{}",
-        nums.first().unwrap().to_string(),
-        nums.last().unwrap().to_string()
-    )
-    .parse()?)
+    let total = |value: fn(&LineTrace) -> Option<i32>| -> i64 {
+        traces.iter().filter_map(value).map(i64::from).sum()
+    };
+    table.push_str(&format!(
+        "{:<6}{:>6}  {:<20}{:>6}\n",
+        "total",
+        total(|trace| trace.part_one),
+        "",
+        total(|trace| trace.part_two)
+    ));
+    if traces
+        .iter()
+        .flat_map(|trace| &trace.tokens)
+        .any(|token| token.overlaps)
+    {
+        table.push_str("\n* shares letters with another token\n");
+    }
+    table
+}
+
+pub fn trace_json(traces: &[LineTrace]) -> String {
+    // Traces only hold strings and numbers so serialising can't fail.
+    serde_json::to_string_pretty(traces).unwrap_or_default() + "\n"
+}
+
+/// The first code point of each run of the ten digits `0` to `9` in a script, the decimal digits
+/// of Unicode 15.
+const DECIMAL_ZEROS: [u32; 68] = [
+    0x0030, 0x0660, 0x06f0, 0x07c0, 0x0966, 0x09e6, 0x0a66, 0x0ae6, 0x0b66, 0x0be6, 0x0c66, 0x0ce6,
+    0x0d66, 0x0de6, 0x0e50, 0x0ed0, 0x0f20, 0x1040, 0x1090, 0x17e0, 0x1810, 0x1946, 0x19d0, 0x1a80,
+    0x1a90, 0x1b50, 0x1bb0, 0x1c40, 0x1c50, 0xa620, 0xa8d0, 0xa900, 0xa9d0, 0xa9f0, 0xaa50, 0xabf0,
+    0xff10, 0x104a0, 0x10d30, 0x11066, 0x110f0, 0x11136, 0x111d0, 0x112f0, 0x11450, 0x114d0,
+    0x11650, 0x116c0, 0x11730, 0x118e0, 0x11950, 0x11c50, 0x11d50, 0x11da0, 0x11f50, 0x16a60,
+    0x16ac0, 0x16b50, 0x1d7ce, 0x1d7d8, 0x1d7e2, 0x1d7ec, 0x1d7f6, 0x1e140, 0x1e2f0, 0x1e4f0,
+    0x1e950, 0x1fbf0,
+];
+
+/// The value of a decimal digit in any script, such as the full width `３` or the Arabic-Indic `٣`.
+pub fn decimal_digit(c: char) -> Option<u32> {
+    let code = u32::from(c);
+    let zero = DECIMAL_ZEROS[DECIMAL_ZEROS
+        .partition_point(|zero| *zero <= code)
+        .checked_sub(1)?];
+    (code - zero < 10).then_some(code - zero)
+}
+
+/// Every decimal digit in any script along with its value, see [`decimal_digit`].
+pub fn decimal_digits() -> impl Iterator<Item = (char, u32)> {
+    DECIMAL_ZEROS.iter().flat_map(|zero| {
+        (0..10).filter_map(move |digit| Some((char::from_u32(zero + digit)?, digit)))
+    })
+}
+
+fn puzzle_words() -> &'static Matcher {
+    static PUZZLE: OnceLock<Matcher> = OnceLock::new();
+    PUZZLE.get_or_init(|| Vocabulary::puzzle().matcher(false, false))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{extract_digits, extract_digits_and_words};
+    use super::{
+        decimal_digit, decimal_digits, extract_digits, extract_digits_and_words, trace, trace_json,
+        trace_table, Day01, NoDigit,
+    };
+    use crate::solution::{ExplainFormat, Options, Solution};
 
     fn test_extract_total_int_case(input: &str, expected: i32) -> Result<(), String> {
         match extract_digits(input) {
@@ -142,7 +304,7 @@ mod tests {
                     Ok(())
                 }
             }
-            Err(err) => Err(format!("{}", err.to_string())),
+            Err(err) => Err(format!("{}", err)),
         }
     }
 
@@ -155,7 +317,7 @@ mod tests {
             ("treb7uchet", 77),
         ]
         .iter()
-        .try_for_each(|(input, expected)| test_extract_total_int_case(*input, *expected))?;
+        .try_for_each(|(input, expected)| test_extract_total_int_case(input, *expected))?;
 
         Ok(())
     }
@@ -169,7 +331,7 @@ mod tests {
                     Ok(())
                 }
             }
-            Err(err) => Err(format!("{}", err.to_string())),
+            Err(err) => Err(format!("{}", err)),
         }
     }
 
@@ -187,8 +349,194 @@ mod tests {
             ("eightwo", 82),
         ]
         .iter()
-        .try_for_each(|(input, expected)| test_extract_total_mixed_case(*input, *expected))?;
+        .try_for_each(|(input, expected)| test_extract_total_mixed_case(input, *expected))?;
 
         Ok(())
     }
+
+    #[test]
+    fn test_extract_no_digit() {
+        ["", "   ", "\t", "abc", "treb uchet"]
+            .iter()
+            .for_each(|line| {
+                assert_eq!(
+                    "malformed record: no digit found",
+                    extract_digits(line).unwrap_err().to_string(),
+                    "{:?}",
+                    line
+                );
+            });
+
+        assert_eq!(
+            "malformed record: no digit found",
+            extract_digits_and_words("oen tow").unwrap_err().to_string()
+        );
+        // A spelled out digit is only a digit in part two.
+        assert!(extract_digits("one").is_err());
+        assert_eq!(11, extract_digits_and_words("one").unwrap());
+    }
+
+    #[test]
+    fn test_no_digit_policy() {
+        let input = "1abc2\n\n  \nabc\ntwo3\n";
+
+        let lines = Day01::parse(input, &Options::default()).unwrap();
+        assert_eq!(
+            "line 2: malformed record: no digit found",
+            Day01::part_one(&lines).unwrap_err().to_string()
+        );
+        assert_eq!(
+            "line 2: malformed record: no digit found",
+            Day01::part_two(&lines).unwrap_err().to_string()
+        );
+
+        let options = Options {
+            no_digit: NoDigit::Skip,
+            ..Options::default()
+        };
+        let lines = Day01::parse(input, &options).unwrap();
+        assert_eq!(12 + 33, Day01::part_one(&lines).unwrap());
+        assert_eq!(12 + 23, Day01::part_two(&lines).unwrap());
+    }
+
+    #[test]
+    fn test_extract_multibyte() {
+        // Letters of any length around the digits, and words, are passed over.
+        [
+            ("é1ü2ß", Some(12), 12),
+            ("🎄two🎅", None, 22),
+            ("ñ7ñoneñ", Some(77), 71),
+            ("日本3語nine", Some(33), 39),
+        ]
+        .iter()
+        .for_each(|(line, digits, words)| {
+            match digits {
+                Some(digits) => assert_eq!(*digits, extract_digits(line).unwrap(), "{}", line),
+                None => assert_eq!(
+                    "malformed record: no digit found",
+                    extract_digits(line).unwrap_err().to_string(),
+                    "{}",
+                    line
+                ),
+            }
+            assert_eq!(*words, extract_digits_and_words(line).unwrap(), "{}", line);
+        });
+    }
+
+    #[test]
+    fn test_decimal_digit() {
+        [
+            ('0', Some(0)),
+            ('9', Some(9)),
+            ('٣', Some(3)),
+            ('۷', Some(7)),
+            ('३', Some(3)),
+            ('５', Some(5)),
+            ('𝟘', Some(0)),
+            ('𝟿', Some(9)),
+            // Kawi and Nag Mundari, new in Unicode 15.
+            ('\u{11f50}', Some(0)),
+            ('\u{11f57}', Some(7)),
+            ('\u{1e4f2}', Some(2)),
+            ('\u{1e4f9}', Some(9)),
+            ('\u{11f5a}', None),
+            ('a', None),
+            ('²', None),
+            ('Ⅻ', None),
+            ('\u{0}', None),
+        ]
+        .iter()
+        .for_each(|(c, want)| assert_eq!(*want, decimal_digit(*c), "{:?}", c));
+
+        for (c, digit) in decimal_digits() {
+            assert!(c.is_numeric(), "{:?}", c);
+            assert_eq!(Some(digit), decimal_digit(c), "{:?}", c);
+        }
+        assert_eq!(680, decimal_digits().count());
+    }
+
+    #[test]
+    fn test_unicode_digits() {
+        let input = "a١b٢c\n５ｏｎｅ\nthree६\n";
+
+        let lines = Day01::parse(input, &Options::default()).unwrap();
+        assert_eq!(
+            "line 1: malformed record: no digit found",
+            Day01::part_one(&lines).unwrap_err().to_string()
+        );
+
+        let options = Options {
+            unicode_digits: true,
+            ..Options::default()
+        };
+        let lines = Day01::parse(input, &options).unwrap();
+        assert_eq!(12 + 55 + 66, Day01::part_one(&lines).unwrap());
+        // Full width letters aren't the ASCII word, so only the full width 5 counts in line 2.
+        assert_eq!(12 + 55 + 36, Day01::part_two(&lines).unwrap());
+
+        // A Kawi 4 and a Nag Mundari 8.
+        let lines = Day01::parse("x\u{11f54}y\u{1e4f8}z\n", &options).unwrap();
+        assert_eq!(48, Day01::part_one(&lines).unwrap());
+    }
+
+    #[test]
+    fn test_trace() {
+        let lines = Day01::parse("xtwone3four\nabc\nnoñe1", &Options::default()).unwrap();
+        let traces = trace(&lines);
+
+        assert_eq!(
+            r#"line  part 1  first     last      part 2  tokens
+1         33  two       four          24  two@2* one@4* 3@7 four@8
+2          -  -         -              -  -
+3         11  1         1             11  1@5
+total     44                          35
+
+* shares letters with another token
+"#,
+            trace_table(&traces)
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&trace_json(&traces)).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "line": 1,
+                "text": "xtwone3four",
+                "part_one": 33,
+                "tokens": [
+                    {"text": "two", "column": 2, "digit": 2, "overlaps": true},
+                    {"text": "one", "column": 4, "digit": 1, "overlaps": true},
+                    {"text": "3", "column": 7, "digit": 3, "overlaps": false},
+                    {"text": "four", "column": 8, "digit": 4, "overlaps": false},
+                ],
+                "first": {"text": "two", "column": 2, "digit": 2, "overlaps": true},
+                "last": {"text": "four", "column": 8, "digit": 4, "overlaps": false},
+                "part_two": 24,
+            }),
+            json[0]
+        );
+        assert_eq!(serde_json::Value::Null, json[1]["part_two"]);
+
+        // Columns count characters rather than bytes, and tokens keep the case they were found in.
+        let options = Options {
+            ignore_case: true,
+            ..Options::default()
+        };
+        let lines = Day01::parse("ñÑEIGHTwo", &options).unwrap();
+        let traces = trace(&lines);
+        assert_eq!(
+            vec![("EIGHT", 3), ("Two", 7)],
+            traces[0]
+                .tokens
+                .iter()
+                .map(|token| (token.text.as_str(), token.column))
+                .collect::<Vec<(&str, usize)>>()
+        );
+        assert_eq!(
+            Some(82),
+            Day01::explain(&lines, ExplainFormat::Json).map(|json| {
+                let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+                json[0]["part_two"].as_i64().unwrap()
+            })
+        );
+    }
 }
@@ -0,0 +1,396 @@
+//! Finding spelled out digits in a single pass over a line, see [`Matcher`].
+
+/// A pattern found in a line, at byte offsets `start..end`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
+    pub value: i32,
+}
+
+impl Match {
+    /// Earlier matches come first, and the shorter of two matches starting at the same place.
+    fn key(&self) -> (usize, usize) {
+        (self.start, self.end)
+    }
+}
+
+/// Finds every occurrence of a set of patterns in a line at once, including overlapping ones such
+/// as the `eight` and `two` in `eightwo`.
+///
+/// This is an Aho-Corasick automaton, a trie of the patterns where each node also knows the
+/// longest suffix of its path that is a prefix of some pattern. Reading a line moves through the
+/// automaton one character at a time without ever going back, so the work is linear in the length
+/// of the line however many patterns there are. A second automaton of the reversed patterns reads
+/// the line from the end, which finds the last match without reading the rest of the line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matcher {
+    forward: Automaton,
+    backward: Automaton,
+    values: Vec<i32>,
+    /// The length of each pattern in characters.
+    lengths: Vec<usize>,
+    longest: usize,
+    ignore_case: bool,
+}
+
+impl Matcher {
+    /// A matcher for each pattern and the value it stands for, ignoring the case of letters when
+    /// `ignore_case` is set. Empty patterns are ignored since they would match everywhere.
+    pub fn new<S: AsRef<str>>(patterns: &[(S, i32)], ignore_case: bool) -> Self {
+        let patterns: Vec<(Vec<char>, i32)> = patterns
+            .iter()
+            .map(|(pattern, value)| {
+                let chars = pattern.as_ref().chars();
+                (chars.map(|c| fold(c, ignore_case)).collect(), *value)
+            })
+            .filter(|(pattern, _): &(Vec<char>, i32)| !pattern.is_empty())
+            .collect();
+        let reversed: Vec<Vec<char>> = patterns
+            .iter()
+            .map(|(pattern, _)| pattern.iter().rev().copied().collect())
+            .collect();
+
+        Matcher {
+            forward: Automaton::new(patterns.iter().map(|(pattern, _)| pattern.as_slice())),
+            backward: Automaton::new(reversed.iter().map(Vec::as_slice)),
+            values: patterns.iter().map(|(_, value)| *value).collect(),
+            lengths: patterns.iter().map(|(pattern, _)| pattern.len()).collect(),
+            longest: patterns
+                .iter()
+                .map(|(pattern, _)| pattern.len())
+                .max()
+                .unwrap_or_default(),
+            ignore_case,
+        }
+    }
+
+    /// Every match in `line` including overlapping ones, ordered by where they start and then by
+    /// length.
+    pub fn find_all(&self, line: &str) -> Vec<Match> {
+        let mut matches: Vec<Match> = vec![];
+        let mut starts: Vec<usize> = vec![];
+        let mut state = 0;
+        for (offset, c) in line.char_indices() {
+            starts.push(offset);
+            state = self.forward.step(state, fold(c, self.ignore_case));
+            matches.extend(
+                self.forward.outputs[state]
+                    .iter()
+                    .map(|pattern| self.match_ending_at(*pattern, &starts, offset + c.len_utf8())),
+            );
+        }
+
+        matches.sort_unstable_by_key(Match::key);
+        matches
+    }
+
+    /// The match that starts first, the same as the first of [`Matcher::find_all`], reading only as
+    /// far into the line as it needs to.
+    pub fn first(&self, line: &str) -> Option<Match> {
+        let mut first: Option<Match> = None;
+        // Anything found from this character on starts after the best so far.
+        let mut stop = usize::MAX;
+        let mut starts: Vec<usize> = vec![];
+        let mut state = 0;
+        for (offset, c) in line.char_indices() {
+            if starts.len() >= stop {
+                break;
+            }
+
+            starts.push(offset);
+            state = self.forward.step(state, fold(c, self.ignore_case));
+            for pattern in &self.forward.outputs[state] {
+                let found = self.match_ending_at(*pattern, &starts, offset + c.len_utf8());
+                if first.is_none_or(|first| found.key() < first.key()) {
+                    first = Some(found);
+                    stop = starts.len() - self.lengths[*pattern as usize] + self.longest;
+                }
+            }
+        }
+
+        first
+    }
+
+    /// The match that starts last, the same as the last starting match of [`Matcher::find_all`]
+    /// and the shortest of those, reading the line from the end.
+    pub fn last(&self, line: &str) -> Option<Match> {
+        // The offset just past each character read, the last character's first.
+        let mut ends: Vec<usize> = vec![];
+        let mut state = 0;
+        for (offset, c) in line.char_indices().rev() {
+            ends.push(offset + c.len_utf8());
+            state = self.backward.step(state, fold(c, self.ignore_case));
+
+            // The reversed patterns found here all start at this character.
+            let shortest = self.backward.outputs[state]
+                .iter()
+                .min_by_key(|pattern| self.lengths[**pattern as usize]);
+            if let Some(pattern) = shortest {
+                let pattern = *pattern as usize;
+                return Some(Match {
+                    start: offset,
+                    end: ends[ends.len() - self.lengths[pattern]],
+                    value: self.values[pattern],
+                });
+            }
+        }
+
+        None
+    }
+
+    /// The two digit value of the first and last match, `None` when nothing matches.
+    pub fn calibration_value(&self, line: &str) -> Option<i32> {
+        Some(self.first(line)?.value * 10 + self.last(line)?.value)
+    }
+
+    /// The match of `pattern` ending at the byte offset `end`, where `starts` holds the offset of
+    /// every character up to it.
+    fn match_ending_at(&self, pattern: u32, starts: &[usize], end: usize) -> Match {
+        let pattern = pattern as usize;
+        Match {
+            start: starts[starts.len() - self.lengths[pattern]],
+            end,
+            value: self.values[pattern],
+        }
+    }
+}
+
+/// Folds upper case letters to lower case when ignoring case, as long as that leaves a single
+/// character, which is all but a handful of letters that no number word uses.
+fn fold(c: char, ignore_case: bool) -> char {
+    if !ignore_case || c.is_lowercase() {
+        return c;
+    }
+
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(lower), None) => lower,
+        _ => c,
+    }
+}
+
+/// The states of the automaton as a dense table of transitions, with the characters that appear in
+/// no pattern sharing a single column.
+#[derive(Clone, Debug, PartialEq)]
+struct Automaton {
+    /// The column of each ASCII character in the table.
+    ascii: [u32; 128],
+    /// The column of every other character in a pattern, sorted by character.
+    others: Vec<(char, u32)>,
+    columns: usize,
+    next: Vec<u32>,
+    /// The patterns that end at each state, including those ending at its suffixes.
+    outputs: Vec<Vec<u32>>,
+}
+
+impl Automaton {
+    const MISSING: u32 = u32::MAX;
+
+    fn new<'a, I: Iterator<Item = &'a [char]> + Clone>(patterns: I) -> Self {
+        let mut automaton = Automaton {
+            ascii: [0; 128],
+            others: vec![],
+            columns: 1,
+            next: vec![],
+            outputs: vec![vec![]],
+        };
+        for c in patterns.clone().flatten() {
+            if automaton.column(*c) == 0 {
+                let column = automaton.columns as u32;
+                if c.is_ascii() {
+                    automaton.ascii[*c as usize] = column;
+                } else {
+                    let at = automaton.others.partition_point(|(other, _)| other < c);
+                    automaton.others.insert(at, (*c, column));
+                }
+                automaton.columns += 1;
+            }
+        }
+        let columns = automaton.columns;
+
+        // Build the trie, leaving the transitions that aren't part of it missing for now.
+        automaton.next = vec![Automaton::MISSING; columns];
+        for (id, pattern) in patterns.enumerate() {
+            let mut state = 0;
+            for c in pattern {
+                let index = state * columns + automaton.column(*c);
+                if automaton.next[index] == Automaton::MISSING {
+                    automaton.next[index] = automaton.outputs.len() as u32;
+                    automaton.next.extend(vec![Automaton::MISSING; columns]);
+                    automaton.outputs.push(vec![]);
+                }
+                state = automaton.next[index] as usize;
+            }
+            automaton.outputs[state].push(id as u32);
+        }
+
+        // Breadth first every state's suffix is known before its children, so the missing
+        // transitions can be filled in from the suffix's and the outputs collected along the way.
+        let mut suffix = vec![0; automaton.outputs.len()];
+        let mut queue = std::collections::VecDeque::from([0]);
+        while let Some(state) = queue.pop_front() {
+            for column in 0..columns {
+                let index = state * columns + column;
+                let fallback = match state {
+                    0 => 0,
+                    _ => automaton.next[suffix[state] * columns + column] as usize,
+                };
+
+                match automaton.next[index] {
+                    Automaton::MISSING => automaton.next[index] = fallback as u32,
+                    child => {
+                        let child = child as usize;
+                        suffix[child] = fallback;
+                        let inherited = automaton.outputs[fallback].clone();
+                        automaton.outputs[child].extend(inherited);
+                        queue.push_back(child);
+                    }
+                }
+            }
+        }
+
+        automaton
+    }
+
+    fn column(&self, c: char) -> usize {
+        if c.is_ascii() {
+            return self.ascii[c as usize] as usize;
+        }
+        match self.others.binary_search_by_key(&c, |(other, _)| *other) {
+            Ok(i) => self.others[i].1 as usize,
+            Err(_) => 0,
+        }
+    }
+
+    fn step(&self, state: usize, c: char) -> usize {
+        self.next[state * self.columns + self.column(c)] as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Match, Matcher};
+    use crate::rng::Rng;
+
+    fn matcher() -> Matcher {
+        Matcher::new(
+            &[
+                ("1", 1),
+                ("one", 1),
+                ("two", 2),
+                ("three", 3),
+                ("eight", 8),
+                ("nine", 9),
+                ("twenty one", 21),
+            ],
+            false,
+        )
+    }
+
+    fn values(matches: &[Match]) -> Vec<(usize, i32)> {
+        matches
+            .iter()
+            .map(|found| (found.start, found.value))
+            .collect()
+    }
+
+    #[test]
+    fn test_find_all() {
+        let matcher = matcher();
+
+        assert_eq!(
+            vec![(0, 8), (4, 2), (6, 1)],
+            values(&matcher.find_all("eightwone"))
+        );
+        assert_eq!(
+            vec![(1, 9), (4, 8)],
+            values(&matcher.find_all("xnineighthree"))[..2]
+        );
+        assert_eq!(
+            vec![(0, 21), (7, 1)],
+            values(&matcher.find_all("twenty one"))
+        );
+        assert_eq!(Vec::<Match>::new(), matcher.find_all("twenty"));
+        assert_eq!(Vec::<Match>::new(), matcher.find_all(""));
+        assert_eq!(
+            Vec::<Match>::new(),
+            Matcher::new::<&str>(&[], false).find_all("one")
+        );
+    }
+
+    #[test]
+    fn test_first_and_last() {
+        let matcher = matcher();
+
+        [
+            ("eightwone", Some((0, 8)), Some((6, 1))),
+            ("twenty one", Some((0, 21)), Some((7, 1))),
+            ("ttwenty onee", Some((1, 21)), Some((8, 1))),
+            ("1", Some((0, 1)), Some((0, 1))),
+            ("abc", None, None),
+        ]
+        .iter()
+        .for_each(|(line, first, last)| {
+            let pair = |found: Option<Match>| found.map(|found| (found.start, found.value));
+            assert_eq!(*first, pair(matcher.first(line)), "{}", line);
+            assert_eq!(*last, pair(matcher.last(line)), "{}", line);
+        });
+
+        // Two patterns starting at the same place, the shorter one is taken from either end.
+        let nested = Matcher::new(&[("ab", 1), ("abcd", 2), ("bc", 3)], false);
+        assert_eq!(1, nested.first("abcd").unwrap().value);
+        assert_eq!(3, nested.last("abcd").unwrap().value);
+        assert_eq!(Some(13), nested.calibration_value("abcd"));
+    }
+
+    #[test]
+    fn test_ignore_case() {
+        let words = [("zéro", 0), ("ŝes", 6), ("neuf", 9)];
+        let sensitive = Matcher::new(&words, false);
+        let insensitive = Matcher::new(&words, true);
+
+        assert_eq!(None, sensitive.first("ZÉRO"));
+        assert_eq!(Vec::<Match>::new(), sensitive.find_all("Neuf"));
+        assert_eq!(
+            vec![(0, 0), (5, 9), (9, 6)],
+            values(&insensitive.find_all("ZÉROneUFŜES"))
+        );
+        // Offsets are into the line as given, even where its letters are longer than the pattern's.
+        assert_eq!(
+            Some(Match {
+                start: 1,
+                end: 6,
+                value: 0
+            }),
+            insensitive.first("xZÉRO")
+        );
+        assert_eq!(Some(96), insensitive.calibration_value("NEUF ŜeS"));
+    }
+
+    #[test]
+    fn test_fast_paths_agree() {
+        // Short random lines over a tiny alphabet hit plenty of overlapping and partial matches.
+        let matcher = matcher();
+        let alphabet: Vec<char> = "1onetwhrig yé".chars().collect();
+        let mut rng = Rng::new(1);
+
+        for _ in 0..2000 {
+            let line: String = (0..rng.below(16))
+                .map(|_| alphabet[rng.below(alphabet.len() as u64) as usize])
+                .collect();
+            let all = matcher.find_all(&line);
+            let last_start = all.last().map(|found| found.start);
+
+            assert_eq!(all.first().copied(), matcher.first(&line), "{}", line);
+            assert_eq!(
+                all.iter()
+                    .find(|found| Some(found.start) == last_start)
+                    .copied(),
+                matcher.last(&line),
+                "{}",
+                line
+            );
+        }
+    }
+}
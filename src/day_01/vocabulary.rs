@@ -0,0 +1,288 @@
+//! The words day 1 reads as digits, see [`Vocabulary`].
+
+use crate::day_01::decimal_digits;
+use crate::day_01::matcher::Matcher;
+use crate::error::{Error, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The vocabularies that can be picked by name, see [`Vocabulary::named`].
+pub const NAMES: [&str; 4] = ["english", "french", "german", "spanish"];
+
+const PUZZLE: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+const ENGLISH: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+const FRENCH: [&str; 10] = [
+    "zéro", "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+];
+const GERMAN: [&str; 10] = [
+    "null", "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+];
+const SPANISH: [&str; 10] = [
+    "cero", "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+];
+
+/// Words that stand for a digit, read alongside the numerals `0` to `9` which are always digits.
+/// A word can be anything that fits on a line, including several words such as `half a dozen`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Vocabulary {
+    words: BTreeMap<String, i32>,
+}
+
+impl Vocabulary {
+    pub fn new() -> Self {
+        Vocabulary::default()
+    }
+
+    /// The words from the puzzle, `one` to `nine` without `zero`.
+    pub fn puzzle() -> Self {
+        Vocabulary::counting_from(1, &PUZZLE)
+    }
+
+    /// One of the built in vocabularies listed in [`NAMES`], each with the words for zero to nine.
+    /// German also has `zwo`, the spoken form of two.
+    pub fn named(name: &str) -> Result<Self> {
+        match name {
+            "english" => Ok(Vocabulary::counting_from(0, &ENGLISH)),
+            "french" => Ok(Vocabulary::counting_from(0, &FRENCH)),
+            "german" => {
+                let mut german = Vocabulary::counting_from(0, &GERMAN);
+                german.add("zwo", 2)?;
+                Ok(german)
+            }
+            "spanish" => Ok(Vocabulary::counting_from(0, &SPANISH)),
+            _ => Err(Error::unsupported(format!(
+                "unknown vocabulary '{}', expected one of {}",
+                name,
+                NAMES.join(", ")
+            ))),
+        }
+    }
+
+    /// Several built in vocabularies at once from a comma separated list of names, such as
+    /// `english,french`.
+    pub fn parse_names(names: &str) -> Result<Self> {
+        let mut vocabulary = Vocabulary::new();
+        for name in names.split(',') {
+            vocabulary.extend(&Vocabulary::named(name.trim())?);
+        }
+        Ok(vocabulary)
+    }
+
+    /// Loads a vocabulary from a TOML file with the digit each word stands for:
+    ///
+    /// ```toml
+    /// one = 1
+    /// "half a dozen" = 6
+    /// ```
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!("unable to read {}: {}", path.display(), err),
+            )
+        })?;
+
+        let words: BTreeMap<String, i64> = toml::from_str(&contents).map_err(|err| {
+            let error = Error::parse(err.message().trim());
+            match err.span() {
+                Some(span) => error.at_offset(&contents, span.start),
+                None => error,
+            }
+        })?;
+        if words.is_empty() {
+            return Err(Error::malformed(format!(
+                "{} has no words in it",
+                path.display()
+            )));
+        }
+
+        let mut vocabulary = Vocabulary::new();
+        for (word, digit) in words {
+            vocabulary.add(&word, digit)?;
+        }
+        Ok(vocabulary)
+    }
+
+    /// Adds `word` standing for `digit`, replacing whatever it stood for before.
+    pub fn add(&mut self, word: &str, digit: i64) -> Result<()> {
+        if word.is_empty() || word.contains(['\n', '\r']) {
+            return Err(Error::malformed(format!(
+                "'{}' isn't a word, words can't be empty or span lines",
+                word.escape_debug()
+            )));
+        }
+        let digit = match i32::try_from(digit) {
+            Ok(digit @ 0..=9) => digit,
+            _ => {
+                return Err(Error::malformed(format!(
+                    "'{}' stands for {}, words can only stand for a digit from 0 to 9",
+                    word, digit
+                )))
+            }
+        };
+
+        self.words.insert(String::from(word), digit);
+        Ok(())
+    }
+
+    /// Adds every word of `other`, which take the place of any of the same words already here.
+    pub fn extend(&mut self, other: &Vocabulary) {
+        self.words.extend(
+            other
+                .words
+                .iter()
+                .map(|(word, digit)| (word.clone(), *digit)),
+        );
+    }
+
+    /// The words in alphabetical order with the digit each stands for.
+    pub fn words(&self) -> impl Iterator<Item = (&str, i32)> {
+        self.words
+            .iter()
+            .map(|(word, digit)| (word.as_str(), *digit))
+    }
+
+    /// A matcher for the numerals and every word, see [`Matcher::new`] for `ignore_case`. The
+    /// numerals are the digits of every script with `unicode_digits`, otherwise `0` to `9`.
+    pub fn matcher(&self, ignore_case: bool, unicode_digits: bool) -> Matcher {
+        let numerals: Vec<(String, i32)> = if unicode_digits {
+            decimal_digits()
+                .map(|(c, digit)| (c.to_string(), digit as i32))
+                .collect()
+        } else {
+            (0..=9).map(|digit| (digit.to_string(), digit)).collect()
+        };
+        let words = self
+            .words()
+            .map(|(word, digit)| (String::from(word), digit));
+        Matcher::new(
+            &numerals.into_iter().chain(words).collect::<Vec<_>>(),
+            ignore_case,
+        )
+    }
+
+    fn counting_from(first: i32, words: &[&str]) -> Self {
+        Vocabulary {
+            words: words
+                .iter()
+                .zip(first..)
+                .map(|(word, digit)| (String::from(*word), digit))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Vocabulary, NAMES};
+    use std::fs;
+
+    #[test]
+    fn test_named() {
+        for name in NAMES {
+            let vocabulary = Vocabulary::named(name).unwrap();
+            let mut digits: Vec<i32> = vocabulary.words().map(|(_, digit)| digit).collect();
+            digits.sort_unstable();
+            digits.dedup();
+
+            assert_eq!((0..=9).collect::<Vec<i32>>(), digits, "{}", name);
+        }
+
+        assert_eq!(
+            "unsupported: unknown vocabulary 'dutch', expected one of english, french, german, spanish",
+            Vocabulary::named("dutch").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_matcher() {
+        [
+            ("english", false, "zero1nine", Some(9)),
+            ("english", false, "Zero1Nine", Some(11)),
+            ("english", true, "Zero1Nine", Some(9)),
+            ("french", false, "zéroundeux", Some(2)),
+            ("german", false, "achtzwo", Some(82)),
+            ("german", true, "FÜNFundNEUN", Some(59)),
+            ("spanish", false, "xcuatrocincox", Some(45)),
+            ("english, spanish", false, "onedos", Some(12)),
+            ("french", false, "one", None),
+        ]
+        .iter()
+        .for_each(|(names, ignore_case, line, want)| {
+            let matcher = Vocabulary::parse_names(names)
+                .unwrap()
+                .matcher(*ignore_case, false);
+            assert_eq!(*want, matcher.calibration_value(line), "{} {}", names, line);
+        });
+
+        // The numerals are always digits, whatever the words.
+        assert_eq!(
+            Some(34),
+            Vocabulary::new()
+                .matcher(false, false)
+                .calibration_value("a3b4c")
+        );
+    }
+
+    #[test]
+    fn test_add() {
+        let mut vocabulary = Vocabulary::puzzle();
+        vocabulary.add("half a dozen", 6).unwrap();
+        vocabulary.add("one", 7).unwrap();
+
+        let matcher = vocabulary.matcher(false, false);
+        assert_eq!(Some(67), matcher.calibration_value("half a dozen or one"));
+
+        [
+            ("", 1, "malformed record: '' isn't a word, words can't be empty or span lines"),
+            (
+                "a\nb",
+                1,
+                "malformed record: 'a\\nb' isn't a word, words can't be empty or span lines",
+            ),
+            (
+                "dozen",
+                12,
+                "malformed record: 'dozen' stands for 12, words can only stand for a digit from 0 to 9",
+            ),
+            (
+                "minus",
+                -1,
+                "malformed record: 'minus' stands for -1, words can only stand for a digit from 0 to 9",
+            ),
+        ]
+        .iter()
+        .for_each(|(word, digit, want)| {
+            assert_eq!(*want, vocabulary.add(word, *digit).unwrap_err().to_string());
+        });
+    }
+
+    #[test]
+    fn test_load() {
+        let path =
+            std::env::temp_dir().join(format!("aoc23_vocabulary_{}.toml", std::process::id()));
+
+        fs::write(&path, "one = 1\n\"half a dozen\" = 6\n").unwrap();
+        let vocabulary = Vocabulary::load(&path).unwrap();
+        assert_eq!(
+            vec![("half a dozen", 6), ("one", 1)],
+            vocabulary.words().collect::<Vec<(&str, i32)>>()
+        );
+
+        fs::write(&path, "one = 1\ntwo = \"2\"\n").unwrap();
+        let got = Vocabulary::load(&path).unwrap_err().to_string();
+        assert!(got.starts_with("line 2, column 7: parse error"), "{}", got);
+
+        fs::write(&path, "ten = 10\n").unwrap();
+        let got = Vocabulary::load(&path).unwrap_err().to_string();
+        assert!(got.contains("'ten' stands for 10"), "{}", got);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
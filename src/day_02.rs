@@ -1,157 +1,807 @@
-use regex::{Captures, Regex};
-use std::env;
-use std::error::Error;
-use std::fs::File;
-use std::io::Read;
-const EXPRESSION: &str =
-    r"(Game\s(?P<game>\d+))|(?P<cubes>(?P<quantity>\d+)\s(?P<colour>\w+))|(?P<sep>;)|(?P<nl>\n)|$";
-const GAME: &str = "game";
-const CUBES: &str = "cubes";
-const QUANTITY: &str = "quantity";
-const COLOUR: &str = "colour";
-const SEP: &str = "sep";
-const NL: &str = "nl";
+//! Day 2: Cube Conundrum. Work out which games are possible with a bag of cubes.
+
+pub mod format;
+pub mod generate;
+pub mod infer;
+pub mod query;
+
+use crate::error::{Error, Result};
+use crate::solution::{Options, Solution};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::Path;
+
 const BLUE: &str = "blue";
 const RED: &str = "red";
 const GREEN: &str = "green";
 
-pub fn totals() -> Result<(i32, i32), Box<dyn Error>> {
-    let file_path = format!(
-        "{}/data/input_day_02.txt",
-        env::current_dir().unwrap().display()
-    );
-    let mut file = File::open(file_path)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-
-    // Hack to ensure there is an empty line at the bottom of the file, this is to avoid doing the
-    // expensive part of parsing regex on every line and instead do it once on the entire file.
-    contents.push('\n');
-
-    let re = Regex::new(EXPRESSION)?;
-    let mut caps: Vec<Captures> = vec![];
-    caps.extend(re.captures_iter(&contents));
-
-    Ok((line_values_total(&caps)?, line_powers_total(&caps)?))
-}
-
-fn line_values_total(caps: &Vec<Captures>) -> Result<i32, Box<dyn Error>> {
-    const MAX_BLUES: i32 = 14;
-    const MAX_REDS: i32 = 12;
-    const MAX_GREENS: i32 = 13;
-    let (mut total, mut game, mut blues, mut reds, mut greens): (i32, i32, i32, i32, i32) =
-        (0, 0, 0, 0, 0);
-
-    for cap in caps.into_iter() {
-        if let Some(value) = cap.name(GAME) {
-            game = value.as_str().parse::<i32>()?;
-        } else if let Some(_) = cap.name(CUBES) {
-            let mut quantity: i32 = 0;
-            if let Some(quantity_match) = cap.name(QUANTITY) {
-                quantity = quantity_match.as_str().parse::<i32>()?;
-            }
+/// The bag from the puzzle, 12 red, 13 green and 14 blue cubes.
+pub fn default_bag() -> CubeSet {
+    [(RED, 12), (GREEN, 13), (BLUE, 14)].into_iter().collect()
+}
 
-            if let Some(colour) = cap.name(COLOUR) {
-                match colour.as_str() {
-                    BLUE => blues += quantity,
-                    RED => reds += quantity,
-                    GREEN => greens += quantity,
-                    _ => {}
-                }
-            }
-            if blues > MAX_BLUES || reds > MAX_REDS || greens > MAX_GREENS {
-                // This game will score zero as one of the maximum cube quantities has been exceeded.
-                game = 0;
-            }
-        } else if let Some(_) = cap.name(SEP) {
-            // We are in a new group, reset quantities.
-            (blues, reds, greens) = (0, 0, 0);
-        } else if let Some(_) = cap.name(NL) {
-            total += game;
+/// Parses a bag written the same way as a draw, such as `12 red, 13 green, 14 blue`.
+pub fn parse_bag(cubes: &str) -> Result<CubeSet> {
+    let mut cursor = Cursor::new(cubes);
+    let bag = parse_draw(&mut cursor, None)?;
+    if !cursor.is_at_end() {
+        return Err(cursor.error("',' or the end of the bag"));
+    }
+
+    Ok(bag)
+}
+
+/// Loads a bag from a TOML file with a count for each colour:
+///
+/// ```toml
+/// red = 12
+/// green = 13
+/// blue = 14
+/// ```
+pub fn load_bag(path: &Path) -> Result<CubeSet> {
+    let contents = fs::read_to_string(path).map_err(|err| {
+        io::Error::new(
+            err.kind(),
+            format!("unable to read {}: {}", path.display(), err),
+        )
+    })?;
 
-            // We are in a new line so reset game score and quantities.
-            (game, blues, reds, greens) = (0, 0, 0, 0);
+    let counts: BTreeMap<String, u32> = toml::from_str(&contents).map_err(|err| {
+        let error = Error::parse(err.message().trim());
+        match err.span() {
+            Some(span) => error.at_offset(&contents, span.start),
+            None => error,
         }
+    })?;
+    if counts.is_empty() {
+        return Err(Error::malformed(format!(
+            "{} has no colours in it",
+            path.display()
+        )));
+    }
+    // A colour the games can't name could never be drawn, leaving every power at zero.
+    if let Some(colour) = counts
+        .keys()
+        .find(|colour| colour.is_empty() || !colour.chars().all(char::is_alphabetic))
+    {
+        return Err(Error::malformed(format!(
+            "'{}' isn't a colour, colours are made of letters only",
+            colour.escape_debug()
+        )));
     }
 
-    Ok(total)
+    Ok(counts.into_iter().collect())
 }
 
-fn line_powers_total(caps: &Vec<Captures>) -> Result<i32, Box<dyn Error>> {
-    let (mut total, mut max_blue, mut max_red, mut max_green): (i32, i32, i32, i32) = (0, 0, 0, 0);
+/// A number of cubes of each colour, colours that aren't in the set count as zero. Serialises as a
+/// map from colour to count.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct CubeSet {
+    counts: BTreeMap<String, u32>,
+}
 
-    for cap in caps.into_iter() {
-        if let Some(_) = cap.name(CUBES) {
-            let mut quantity: i32 = 0;
-            if let Some(quantity_match) = cap.name(QUANTITY) {
-                quantity = quantity_match.as_str().parse::<i32>()?;
-            }
+impl CubeSet {
+    pub fn new() -> Self {
+        CubeSet::default()
+    }
 
-            if let Some(colour) = cap.name(COLOUR) {
-                match colour.as_str() {
-                    BLUE => {
-                        if quantity > max_blue {
-                            max_blue = quantity;
-                        }
-                    }
-                    RED => {
-                        if quantity > max_red {
-                            max_red = quantity;
-                        }
-                    }
-                    GREEN => {
-                        if quantity > max_green {
-                            max_green = quantity;
-                        }
-                    }
-                    _ => {}
-                }
-            }
-        } else if let Some(_) = cap.name(NL) {
-            total += max_blue * max_red * max_green;
+    pub fn contains(&self, colour: &str) -> bool {
+        self.counts.contains_key(colour)
+    }
+
+    pub fn get(&self, colour: &str) -> u32 {
+        self.counts.get(colour).copied().unwrap_or_default()
+    }
+
+    /// Adds `count` cubes of `colour` to any already in the set.
+    pub fn add(&mut self, colour: &str, count: u32) {
+        *self.counts.entry(String::from(colour)).or_default() += count;
+    }
+
+    /// The colours and their counts in alphabetical order of colour.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.counts
+            .iter()
+            .map(|(colour, count)| (colour.as_str(), *count))
+    }
 
-            // We are in a new line, reset quantities.
-            (max_blue, max_red, max_green) = (0, 0, 0);
+    pub fn colours(&self) -> Vec<&str> {
+        self.counts.keys().map(String::as_str).collect()
+    }
+
+    /// Whether every colour in this set fits in `other`.
+    pub fn fits_in(&self, other: &CubeSet) -> bool {
+        self.iter()
+            .all(|(colour, count)| count <= other.get(colour))
+    }
+
+    /// The larger count of each colour in either set.
+    pub fn union(&self, other: &CubeSet) -> CubeSet {
+        let mut union = self.clone();
+        for (colour, count) in other.iter() {
+            let max = union.counts.entry(String::from(colour)).or_default();
+            *max = (*max).max(count);
         }
+        union
     }
 
-    Ok(total)
+    /// The counts of `colours` multiplied together, failing with an overflow error when that
+    /// doesn't fit in a u64.
+    pub fn power(&self, colours: &[&str]) -> Result<u64> {
+        colours
+            .iter()
+            .try_fold(1u64, |product, colour| {
+                product.checked_mul(u64::from(self.get(colour)))
+            })
+            .ok_or_else(|| Error::overflow("the power doesn't fit in a u64"))
+    }
+}
+
+impl<S: Into<String>> FromIterator<(S, u32)> for CubeSet {
+    fn from_iter<I: IntoIterator<Item = (S, u32)>>(iter: I) -> Self {
+        let mut set = CubeSet::new();
+        for (colour, count) in iter {
+            set.add(&colour.into(), count);
+        }
+        set
+    }
+}
+
+/// A single handful of cubes shown during a game.
+pub type Draw = CubeSet;
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Game {
+    pub id: u32,
+    pub draws: Vec<Draw>,
+}
+
+impl Game {
+    /// Whether every draw could have come out of `bag`.
+    pub fn is_possible(&self, bag: &CubeSet) -> bool {
+        self.draws.iter().all(|draw| draw.fits_in(bag))
+    }
+
+    /// The smallest bag every draw could have come out of.
+    pub fn minimum_set(&self) -> CubeSet {
+        self.draws
+            .iter()
+            .fold(CubeSet::new(), |minimum, draw| minimum.union(draw))
+    }
+}
+
+/// The parsed games along with the bag they are played with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Games {
+    pub games: Vec<Game>,
+    pub bag: CubeSet,
+}
+
+/// Part one sums the ids of possible games, part two sums the power of each game's minimum bag.
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+    const EXAMPLE: &'static str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+";
+
+    type Input = Games;
+    type Answer = u64;
+
+    fn parse(input: &str, options: &Options) -> Result<Self::Input> {
+        let bag = options.bag.clone().unwrap_or_else(default_bag);
+        Ok(Games {
+            games: parse_games(input, &bag)?,
+            bag,
+        })
+    }
+
+    fn part_one(input: &Self::Input) -> Result<Self::Answer> {
+        line_values_total(&input.games, &input.bag)
+    }
+
+    fn part_two(input: &Self::Input) -> Result<Self::Answer> {
+        line_powers_total(&input.games, &input.bag)
+    }
+
+    fn stream(
+        reader: &mut dyn BufRead,
+        options: &Options,
+    ) -> Option<Result<(Self::Answer, Self::Answer)>> {
+        let bag = options.bag.clone().unwrap_or_else(default_bag);
+        Some(totals(reader, &bag).and_then(|totals| match totals.games {
+            0 => Err(Error::empty_input()),
+            _ => Ok((totals.possible, totals.power)),
+        }))
+    }
+}
+
+/// Parses one game per line, skipping blank lines. Every colour drawn has to be in `bag`.
+pub fn parse_games(contents: &str, bag: &CubeSet) -> Result<Vec<Game>> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| parse_game(line, bag).map_err(|err| err.at_line(i + 1)))
+        .collect()
+}
+
+/// Parses a single `Game <id>: <count> <colour>, ...; ...` record, every colour drawn has to be in
+/// `bag`. Errors have the column of the first token that doesn't fit the grammar.
+pub fn parse_game(line: &str, bag: &CubeSet) -> Result<Game> {
+    let mut cursor = Cursor::new(line);
+
+    cursor.skip_spaces();
+    cursor.expect("Game", "'Game'")?;
+    cursor.skip_spaces();
+    let id = cursor.number("a game id")?;
+    cursor.skip_spaces();
+    cursor.expect(":", "':' after the game id")?;
+
+    let mut draws: Vec<Draw> = vec![];
+    loop {
+        draws.push(parse_draw(&mut cursor, Some(bag))?);
+        if cursor.is_at_end() {
+            break;
+        }
+        cursor.expect(";", "',', ';' or the end of the line")?;
+    }
+
+    Ok(Game { id, draws })
+}
+
+/// Parses `<count> <colour>, ...` up to the next `;`, rejecting a colour that appears twice or
+/// isn't in `bag` when there is one.
+fn parse_draw(cursor: &mut Cursor, bag: Option<&CubeSet>) -> Result<Draw> {
+    let mut draw = Draw::new();
+    loop {
+        cursor.skip_spaces();
+        let count = cursor.number("a cube count")?;
+        if cursor.skip_spaces() == 0 {
+            return Err(cursor.error("a space after the cube count"));
+        }
+
+        let column = cursor.column();
+        let colour = cursor.take_while(char::is_alphabetic);
+        if colour.is_empty() {
+            return Err(cursor.error("a colour"));
+        }
+        if let Some(bag) = bag.filter(|bag| !bag.contains(colour)) {
+            return Err(Error::malformed(format!(
+                "unknown colour '{}', the bag holds {}",
+                colour,
+                bag.colours().join(", ")
+            ))
+            .at_column(column));
+        }
+        if draw.contains(colour) {
+            return Err(Error::malformed(format!(
+                "duplicate colour '{}' in the same draw",
+                colour
+            ))
+            .at_column(column));
+        }
+        draw.add(colour, count);
+
+        cursor.skip_spaces();
+        if !cursor.eat(",") {
+            return Ok(draw);
+        }
+    }
+}
+
+/// A position in a single record, so that errors can say where they happened.
+struct Cursor<'a> {
+    line: &'a str,
+    offset: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(line: &'a str) -> Self {
+        Cursor { line, offset: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.line[self.offset..]
+    }
+
+    fn column(&self) -> usize {
+        self.line[..self.offset].chars().count() + 1
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.rest().trim().is_empty()
+    }
+
+    /// Moves past `literal` if it is next, returning whether it was.
+    fn eat(&mut self, literal: &str) -> bool {
+        if self.rest().starts_with(literal) {
+            self.offset += literal.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, literal: &str, expected: &str) -> Result<()> {
+        if self.eat(literal) {
+            Ok(())
+        } else {
+            Err(self.error(expected))
+        }
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c: char| !predicate(c)).unwrap_or(rest.len());
+        self.offset += len;
+        &rest[..len]
+    }
+
+    /// Skips any whitespace, returning how many bytes were skipped.
+    fn skip_spaces(&mut self) -> usize {
+        self.take_while(char::is_whitespace).len()
+    }
+
+    fn number(&mut self, expected: &str) -> Result<u32> {
+        let column = self.column();
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        if digits.is_empty() {
+            return Err(self.error(expected));
+        }
+
+        // Any run of digits is accepted here so may still be too large for a u32.
+        digits.parse::<u32>().map_err(|err| {
+            Error::parse(format!("invalid number '{}': {}", digits, err)).at_column(column)
+        })
+    }
+
+    /// An error at the current position saying what was expected and what was found instead.
+    fn error(&self, expected: &str) -> Error {
+        let rest = self.rest();
+        let found = match rest.chars().next() {
+            None => String::from("the end of the line"),
+            Some(c) if c.is_alphanumeric() => format!(
+                "'{}'",
+                rest.split(|c: char| !c.is_alphanumeric())
+                    .next()
+                    .unwrap_or_default()
+            ),
+            Some(c) => format!("'{}'", c),
+        };
+
+        Error::malformed(format!("expected {}, found {}", expected, found)).at_column(self.column())
+    }
+}
+
+/// Both answers for a set of games, see [`totals`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Totals {
+    pub games: usize,
+    /// The sum of the ids of the possible games, the same as [`line_values_total`].
+    pub possible: u64,
+    /// The sum of the powers of the games, the same as [`line_powers_total`].
+    pub power: u64,
+}
+
+/// Scores the games from `reader` one line at a time, so only a single game is held in memory
+/// however long the input is.
+pub fn totals<R: BufRead>(mut reader: R, bag: &CubeSet) -> Result<Totals> {
+    let colours = bag.colours();
+    let mut totals = Totals::default();
+    let mut line = String::new();
+
+    for number in 1.. {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let game = parse_game(line.trim_end_matches(['\n', '\r']), bag)
+            .map_err(|err| err.at_line(number))?;
+        totals.games += 1;
+        if game.is_possible(bag) {
+            totals.possible += u64::from(game.id);
+        }
+        totals.power = game
+            .minimum_set()
+            .power(&colours)
+            .and_then(|power| {
+                totals
+                    .power
+                    .checked_add(power)
+                    .ok_or_else(|| Error::overflow("the power sum doesn't fit in a u64"))
+            })
+            .map_err(|err| err.at_line(number))?;
+    }
+
+    Ok(totals)
+}
+
+/// The sum of the ids of games possible with `bag`, see [`default_bag`] for the puzzle's bag.
+pub fn line_values_total(games: &[Game], bag: &CubeSet) -> Result<u64> {
+    Ok(games
+        .iter()
+        .filter(|game| game.is_possible(bag))
+        .map(|game| u64::from(game.id))
+        .sum())
+}
+
+/// The sum of each game's power, the counts of each of the bag's colours in its smallest possible
+/// bag multiplied together.
+pub fn line_powers_total(games: &[Game], bag: &CubeSet) -> Result<u64> {
+    let colours = bag.colours();
+    games.iter().try_fold(0u64, |total, game| {
+        total
+            .checked_add(game.minimum_set().power(&colours)?)
+            .ok_or_else(|| Error::overflow("the power sum doesn't fit in a u64"))
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{line_powers_total, line_values_total, EXPRESSION};
-    use regex::{Captures, Regex};
+    use super::{
+        default_bag, line_powers_total, line_values_total, load_bag, parse_bag, parse_game,
+        parse_games, totals, CubeSet, Game, Totals,
+    };
+    use crate::error::ErrorKind;
+    use std::fs;
+    use std::io::{self, BufRead, Read};
 
-    #[test]
-    fn test_line_values_total() {
-        let input = r#"Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+    const INPUT: &str = r#"Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
 Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
 Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
 Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
 Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
 "#;
-        let mut matches: Vec<Captures> = vec![];
-        matches.extend(Regex::new(EXPRESSION).unwrap().captures_iter(&input));
 
-        let got = line_values_total(&matches).unwrap();
+    #[test]
+    fn test_line_values_total() {
+        let games = parse_games(INPUT, &default_bag()).unwrap();
+
+        let got = line_values_total(&games, &default_bag()).unwrap();
 
         assert_eq!(8, got);
     }
 
     #[test]
     fn test_line_powers_total() {
-        let input = r#"Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
-Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
-Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
-Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
-"#;
-        let mut matches: Vec<Captures> = vec![];
-        matches.extend(Regex::new(EXPRESSION).unwrap().captures_iter(&input));
+        let games = parse_games(INPUT, &default_bag()).unwrap();
 
-        let got = line_powers_total(&matches).unwrap();
+        let got = line_powers_total(&games, &default_bag()).unwrap();
 
         assert_eq!(2286, got);
     }
+
+    #[test]
+    fn test_other_bags() {
+        let input =
+            "Game 1: 3 blue, 1 purple; 2 red, 1 green\nGame 2: 5 purple, 1 green\nGame 3: 2 blue";
+
+        [
+            // Only game 1 has every colour, 3 blue x 1 green x 1 purple x 2 red.
+            ("12 red, 13 green, 14 blue, 4 purple", 4, 6),
+            ("12 red, 13 green, 14 blue, 5 purple", 6, 6),
+            ("1 green, 3 blue, 2 red, 5 purple", 6, 6),
+            ("1 green, 2 blue, 2 red, 5 purple", 5, 6),
+        ]
+        .iter()
+        .for_each(|(bag, possible, power)| {
+            let bag = parse_bag(bag).unwrap();
+            let games = parse_games(input, &bag).unwrap();
+
+            assert_eq!(*possible, line_values_total(&games, &bag).unwrap());
+            assert_eq!(*power, line_powers_total(&games, &bag).unwrap());
+        });
+
+        // With only blue in the bag game 3 is the only one that can be parsed.
+        let bag = parse_bag("2 blue").unwrap();
+        let games = parse_games("Game 3: 2 blue; 1 blue", &bag).unwrap();
+        assert_eq!(3, line_values_total(&games, &bag).unwrap());
+        assert_eq!(2, line_powers_total(&games, &bag).unwrap());
+    }
+
+    #[test]
+    fn test_parse_game() {
+        let got = parse_game("Game 12: 3 blue, 4 red; 2 green", &default_bag()).unwrap();
+
+        assert_eq!(
+            Game {
+                id: 12,
+                draws: vec![
+                    [("blue", 3), ("red", 4)].into_iter().collect(),
+                    [("green", 2)].into_iter().collect(),
+                ],
+            },
+            got
+        );
+        assert_eq!(
+            [("blue", 3), ("green", 2), ("red", 4)]
+                .into_iter()
+                .collect::<CubeSet>(),
+            got.minimum_set()
+        );
+    }
+
+    fn test_parse_games_errors_case(input: &str, want: &str) -> Result<(), String> {
+        match parse_games(input, &default_bag()) {
+            Ok(games) => Err(format!("{}: want error {}, got {:?}", input, want, games)),
+            Err(err) => {
+                let got = err.to_string();
+                if got != want {
+                    Err(format!("{}: want {}, got {}", input, want, got))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_games_errors() -> Result<(), String> {
+        [
+            (
+                "Game 1: 3 blue\nGame 2 3 blue",
+                "line 2, column 8: malformed record: expected ':' after the game id, found '3'",
+            ),
+            (
+                "Game : 3 blue",
+                "line 1, column 6: malformed record: expected a game id, found ':'",
+            ),
+            (
+                "Game x: 3 blue",
+                "line 1, column 6: malformed record: expected a game id, found 'x'",
+            ),
+            (
+                "Round 1: 3 blue",
+                "line 1, column 1: malformed record: expected 'Game', found 'Round'",
+            ),
+            (
+                "Game 1:",
+                "line 1, column 8: malformed record: expected a cube count, found the end of the line",
+            ),
+            (
+                "Game 1: 3 blue;; 2 red",
+                "line 1, column 16: malformed record: expected a cube count, found ';'",
+            ),
+            (
+                "Game 1: 3 blue; 2 red;",
+                "line 1, column 23: malformed record: expected a cube count, found the end of the line",
+            ),
+            (
+                "Game 1: 3 blue, 2 red,",
+                "line 1, column 23: malformed record: expected a cube count, found the end of the line",
+            ),
+            (
+                "Game 1: 3 blue, red",
+                "line 1, column 17: malformed record: expected a cube count, found 'red'",
+            ),
+            (
+                "Game 1: 3 blu e",
+                "line 1, column 11: malformed record: unknown colour 'blu', the bag holds blue, green, red",
+            ),
+            (
+                "Game 1: 3 blue e",
+                "line 1, column 16: malformed record: expected ',', ';' or the end of the line, found 'e'",
+            ),
+            (
+                "Game 1: 3blue",
+                "line 1, column 10: malformed record: expected a space after the cube count, found 'blue'",
+            ),
+            (
+                "Game 1: 3 blue, 2 red, 1 blue; 4 green",
+                "line 1, column 26: malformed record: duplicate colour 'blue' in the same draw",
+            ),
+            (
+                "Game 1: 3 blue\nGame 2: 1 red, 2 purple",
+                "line 2, column 18: malformed record: unknown colour 'purple', the bag holds blue, green, red",
+            ),
+            (
+                "Game 99999999999: 3 blue",
+                "line 1, column 6: parse error: invalid number '99999999999': number too large to fit in target type",
+            ),
+        ]
+        .iter()
+        .try_for_each(|(input, want)| test_parse_games_errors_case(input, want))?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_games_spacing() {
+        // Spacing around the separators doesn't matter, only that the count and colour are apart.
+        let games = parse_games(
+            "  Game   7 :3 blue ,4 red;1 green\t\nGame 8: 1 red  \n",
+            &default_bag(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![7, 8],
+            games.iter().map(|game| game.id).collect::<Vec<u32>>()
+        );
+        assert_eq!(2, games[0].draws.len());
+    }
+
+    #[test]
+    fn test_parse_bag() {
+        assert_eq!(
+            default_bag(),
+            parse_bag("14 blue, 12 red, 13 green").unwrap()
+        );
+        assert_eq!(
+            "column 7: malformed record: expected ',' or the end of the bag, found ';'",
+            parse_bag("12 red; 13 green").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_minimum_set_power() {
+        // A colour that never comes out of the bag leaves the power at zero.
+        let game = parse_game("Game 1: 3 blue, 4 red; 6 blue", &default_bag()).unwrap();
+
+        assert_eq!(
+            0,
+            game.minimum_set().power(&default_bag().colours()).unwrap()
+        );
+        assert_eq!(24, game.minimum_set().power(&["blue", "red"]).unwrap());
+    }
+
+    #[test]
+    fn test_power_overflow() {
+        let bag = parse_bag("1 red, 1 green, 1 blue").unwrap();
+        let game = "Game 1: 4294967295 red, 4294967295 green, 4294967295 blue";
+        let games = parse_games(game, &bag).unwrap();
+
+        let err = games[0].minimum_set().power(&bag.colours()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Overflow(_)), "{:?}", err);
+        assert_eq!(
+            "overflow: the power doesn't fit in a u64",
+            line_powers_total(&games, &bag).unwrap_err().to_string()
+        );
+
+        // Each power fits but their sum doesn't.
+        let games = parse_games(
+            "Game 1: 4294967295 red, 4294967295 green\nGame 2: 4294967295 red, 4294967295 green",
+            &parse_bag("1 red, 1 green").unwrap(),
+        )
+        .unwrap();
+        let err = line_powers_total(&games, &parse_bag("1 red, 1 green").unwrap()).unwrap_err();
+        assert_eq!(
+            "overflow: the power sum doesn't fit in a u64",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_load_bag() {
+        let path = std::env::temp_dir().join(format!("aoc23_bag_{}.toml", std::process::id()));
+
+        fs::write(&path, "red = 12\ngreen = 13\nblue = 14\n").unwrap();
+        assert_eq!(default_bag(), load_bag(&path).unwrap());
+
+        fs::write(&path, "red = 12\ngreen = \"lots\"\n").unwrap();
+        let got = load_bag(&path).unwrap_err().to_string();
+        assert!(got.starts_with("line 2, column 9: parse error"), "{}", got);
+
+        for colour in ["light blue", "", "red2"] {
+            fs::write(&path, format!("red = 12\n\"{}\" = 3\n", colour)).unwrap();
+            let err = load_bag(&path).unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::Malformed(_)), "{}", err);
+            assert!(
+                err.to_string().ends_with(&format!(
+                    "'{}' isn't a colour, colours are made of letters only",
+                    colour
+                )),
+                "{}",
+                err
+            );
+        }
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_totals() {
+        let want = Totals {
+            games: 5,
+            possible: 8,
+            power: 2286,
+        };
+        assert_eq!(want, totals(INPUT.as_bytes(), &default_bag()).unwrap());
+
+        // Windows line endings and blank lines read the same.
+        let crlf = INPUT.replace('\n', "\r\n\r\n");
+        assert_eq!(want, totals(crlf.as_bytes(), &default_bag()).unwrap());
+
+        let err = totals(
+            "Game 1: 1 red\n\nGame 2: 1 rod\n".as_bytes(),
+            &default_bag(),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.starts_with("line 3, column 11: "), "{}", err);
+
+        // The game that takes the power sum past a u64 is the one reported.
+        let bag = parse_bag("1 red, 1 green").unwrap();
+        let game = "Game 1: 4294967295 red, 4294967295 green\n";
+        assert_eq!(
+            "line 2: overflow: the power sum doesn't fit in a u64",
+            totals(game.repeat(2).as_bytes(), &bag)
+                .unwrap_err()
+                .to_string()
+        );
+        let game = "Game 1: 4294967295 red, 4294967295 green, 2 blue\n";
+        assert_eq!(
+            "line 1: overflow: the power doesn't fit in a u64",
+            totals(
+                game.as_bytes(),
+                &parse_bag("1 red, 1 green, 1 blue").unwrap()
+            )
+            .unwrap_err()
+            .to_string()
+        );
+    }
+
+    /// An endless input made of the same game over and over, without ever being held in memory.
+    struct Repeat {
+        game: &'static [u8],
+        remaining: usize,
+        offset: usize,
+    }
+
+    impl Read for Repeat {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.fill_buf()?.len().min(buf.len());
+            buf[..n].copy_from_slice(&self.game[self.offset..self.offset + n]);
+            self.consume(n);
+            Ok(n)
+        }
+    }
+
+    impl BufRead for Repeat {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            if self.remaining == 0 {
+                return Ok(&[]);
+            }
+            Ok(&self.game[self.offset..])
+        }
+
+        fn consume(&mut self, amount: usize) {
+            self.offset += amount;
+            if self.offset == self.game.len() {
+                self.offset = 0;
+                self.remaining -= 1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_totals_streams() {
+        let repeat = Repeat {
+            game: b"Game 3: 1 red, 2 green; 3 blue\n",
+            remaining: 50_000,
+            offset: 0,
+        };
+
+        assert_eq!(
+            Totals {
+                games: 50_000,
+                possible: 150_000,
+                power: 300_000,
+            },
+            totals(repeat, &default_bag()).unwrap()
+        );
+    }
 }
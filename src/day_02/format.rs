@@ -0,0 +1,271 @@
+//! Writing games back out, as canonical puzzle input with [`format_games`] or as JSON with
+//! [`to_json`], both of which read back to the same games.
+
+use crate::day_02::{CubeSet, Game};
+use crate::error::{Error, Result};
+
+/// Formats a game the way the puzzle writes it, `Game 1: 3 blue, 4 red; 2 green`, with the colours
+/// of each draw in alphabetical order.
+pub fn format_game(game: &Game) -> String {
+    let draws: Vec<String> = game
+        .draws
+        .iter()
+        .map(|draw| {
+            draw.iter()
+                .map(|(colour, count)| format!("{} {}", count, colour))
+                .collect::<Vec<String>>()
+                .join(", ")
+        })
+        .collect();
+
+    format!("Game {}: {}", game.id, draws.join("; "))
+}
+
+/// One game per line in the order given, each line ending in a newline.
+pub fn format_games(games: &[Game]) -> String {
+    games.iter().map(|game| format_game(game) + "\n").collect()
+}
+
+/// The games as a JSON array, each game an object with its id and a list of draws mapping colour
+/// to count:
+///
+/// ```json
+/// [{ "id": 1, "draws": [{ "blue": 3, "red": 4 }, { "green": 2 }] }]
+/// ```
+pub fn to_json(games: &[Game]) -> String {
+    // Only strings and numbers are held so serialising can't fail.
+    serde_json::to_string_pretty(games).unwrap_or_default() + "\n"
+}
+
+/// Reads games written by [`to_json`], holding them to the same rules as the puzzle input. Every
+/// game needs at least one draw, every draw at least one colour and every colour has to be in
+/// `bag`.
+pub fn from_json(json: &str, bag: &CubeSet) -> Result<Vec<Game>> {
+    let games: Vec<Game> = serde_json::from_str(json).map_err(|err| {
+        // serde_json puts the position at the end of its message, it is kept separately instead.
+        let message = err.to_string();
+        let position = format!(" at line {} column {}", err.line(), err.column());
+        let error =
+            Error::parse(message.strip_suffix(&position).unwrap_or(&message)).at_line(err.line());
+        // Errors about a whole value rather than a character in it have no column.
+        match err.column() {
+            0 => error,
+            column => error.at_column(column),
+        }
+    })?;
+
+    for game in &games {
+        if game.draws.is_empty() {
+            return Err(Error::malformed(format!("game {} has no draws", game.id)));
+        }
+        for (i, draw) in game.draws.iter().enumerate() {
+            if draw.iter().next().is_none() {
+                return Err(Error::malformed(format!(
+                    "game {}, draw {} has no cubes",
+                    game.id,
+                    i + 1
+                )));
+            }
+            if let Some(colour) = draw.colours().into_iter().find(|c| !bag.contains(c)) {
+                return Err(Error::malformed(format!(
+                    "game {}, draw {}: unknown colour '{}', the bag holds {}",
+                    game.id,
+                    i + 1,
+                    colour,
+                    bag.colours().join(", ")
+                )));
+            }
+        }
+    }
+
+    Ok(games)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_game, format_games, from_json, to_json};
+    use crate::day_02::{default_bag, parse_bag, parse_game, parse_games, CubeSet, Draw, Game};
+    use crate::rng::Rng;
+
+    /// A bag of up to eight colours with made up names, always including the puzzle's.
+    fn random_bag(rng: &mut Rng) -> CubeSet {
+        let mut bag = default_bag();
+        for _ in 0..rng.below(6) {
+            let colour: String = (0..rng.between(1, 8))
+                .map(|_| char::from(b'a' + rng.below(26) as u8))
+                .collect();
+            bag.add(&colour, 1);
+        }
+        bag
+    }
+
+    /// Mostly small counts and ids, with the odd one at the edges of a u32.
+    fn random_number(rng: &mut Rng) -> u32 {
+        match rng.below(10) {
+            0 => 0,
+            1 => u32::MAX,
+            _ => rng.between(1, 20) as u32,
+        }
+    }
+
+    fn random_game(rng: &mut Rng, bag: &CubeSet) -> Game {
+        let draws = (0..rng.between(1, 6))
+            .map(|_| {
+                let mut colours = bag.colours();
+                rng.shuffle(&mut colours);
+                let picked = rng.between(1, colours.len() as u64) as usize;
+                colours[..picked]
+                    .iter()
+                    .map(|colour| (*colour, random_number(rng)))
+                    .collect::<Draw>()
+            })
+            .collect();
+
+        Game {
+            id: random_number(rng),
+            draws,
+        }
+    }
+
+    /// Writes `game` the long way round, with the colours in any order and spaces wherever the
+    /// grammar allows them.
+    fn untidy_game(rng: &mut Rng, game: &Game) -> String {
+        let space = |rng: &mut Rng, at_least: u64| " ".repeat(rng.between(at_least, 2) as usize);
+        let mut line = format!("{}Game {}{}:", space(rng, 0), game.id, space(rng, 0));
+        for (i, draw) in game.draws.iter().enumerate() {
+            if i > 0 {
+                line.push(';');
+            }
+            let mut cubes: Vec<(&str, u32)> = draw.iter().collect();
+            rng.shuffle(&mut cubes);
+            for (j, (colour, count)) in cubes.into_iter().enumerate() {
+                if j > 0 {
+                    line.push_str(&format!("{},", space(rng, 0)));
+                }
+                line.push_str(&format!(
+                    "{}{}{}{}",
+                    space(rng, 0),
+                    count,
+                    space(rng, 1),
+                    colour
+                ));
+            }
+        }
+        line
+    }
+
+    #[test]
+    fn test_format_game() {
+        let game = parse_game("  Game 7 :4 red ,3 blue;1 green  ", &default_bag()).unwrap();
+
+        assert_eq!("Game 7: 3 blue, 4 red; 1 green", format_game(&game));
+        assert_eq!(
+            "Game 1: 3 blue, 4 red; 2 green, 1 red\nGame 2: 0 blue\n",
+            format_games(
+                &parse_games(
+                    "Game 1: 4 red, 3 blue; 1 red, 2 green\n\nGame 2: 0 blue",
+                    &default_bag()
+                )
+                .unwrap()
+            )
+        );
+        assert_eq!("", format_games(&[]));
+    }
+
+    #[test]
+    fn test_format_round_trip() {
+        let mut rng = Rng::new(2023);
+        for _ in 0..200 {
+            let bag = random_bag(&mut rng);
+            let game = random_game(&mut rng, &bag);
+            let formatted = format_game(&game);
+
+            assert_eq!(game, parse_game(&formatted, &bag).unwrap(), "{}", formatted);
+
+            // However the game was written it comes out formatted the same way.
+            let untidy = untidy_game(&mut rng, &game);
+            let reparsed = parse_game(&untidy, &bag).unwrap();
+            assert_eq!(game, reparsed, "{}", untidy);
+            assert_eq!(formatted, format_game(&reparsed), "{}", untidy);
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut rng = Rng::new(25);
+        for _ in 0..50 {
+            let bag = random_bag(&mut rng);
+            let games: Vec<Game> = (0..rng.below(8))
+                .map(|_| random_game(&mut rng, &bag))
+                .collect();
+            let json = to_json(&games);
+
+            assert_eq!(games, from_json(&json, &bag).unwrap(), "{}", json);
+            assert_eq!(
+                games,
+                parse_games(&format_games(&games), &bag).unwrap(),
+                "{}",
+                json
+            );
+        }
+    }
+
+    #[test]
+    fn test_to_json() {
+        let games = parse_games("Game 1: 4 red, 3 blue; 2 green", &default_bag()).unwrap();
+
+        assert_eq!(
+            r#"[{"id":1,"draws":[{"blue":3,"red":4},{"green":2}]}]"#,
+            to_json(&games).split_whitespace().collect::<String>()
+        );
+    }
+
+    fn test_from_json_errors_case(json: &str, want: &str) -> Result<(), String> {
+        let bag = parse_bag("1 red, 1 blue").map_err(|err| err.to_string())?;
+        match from_json(json, &bag) {
+            Ok(games) => Err(format!("{}: want error {}, got {:?}", json, want, games)),
+            Err(err) => {
+                let got = err.to_string();
+                if got != want {
+                    Err(format!("{}: want {}, got {}", json, want, got))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_json_errors() -> Result<(), String> {
+        [
+            (
+                r#"[{"id": 1, "draws": []}]"#,
+                "malformed record: game 1 has no draws",
+            ),
+            (
+                r#"[{"id": 1, "draws": [{"red": 1}, {}]}]"#,
+                "malformed record: game 1, draw 2 has no cubes",
+            ),
+            (
+                r#"[{"id": 2, "draws": [{"red": 1, "green": 1}]}]"#,
+                "malformed record: game 2, draw 1: unknown colour 'green', the bag holds blue, red",
+            ),
+            (
+                "[{\"id\": 1,\n \"draws\": [{\"red\": -1}]}]",
+                "line 2, column 21: parse error: invalid value: integer `-1`, expected u32",
+            ),
+            (
+                r#"[{"id": 1, "draws": [], "winner": true}]"#,
+                "line 1, column 32: parse error: unknown field `winner`, expected `id` or `draws`",
+            ),
+            (
+                r#"{"id": 1}"#,
+                "line 1: parse error: invalid type: map, expected a sequence",
+            ),
+        ]
+        .iter()
+        .try_for_each(|(json, want)| test_from_json_errors_case(json, want))?;
+
+        Ok(())
+    }
+}
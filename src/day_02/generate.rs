@@ -0,0 +1,259 @@
+//! Made up day 2 inputs of any size along with their answers, see [`generate`].
+
+use crate::day_02::CubeSet;
+use crate::error::{Error, Result};
+use crate::rng::Rng;
+use std::io::Write;
+
+/// The colours a generated bag is made of, the puzzle's own first.
+pub const COLOURS: [&str; 8] = [
+    "red", "green", "blue", "yellow", "purple", "orange", "white", "black",
+];
+
+/// The shape of a generated input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Config {
+    pub games: usize,
+    /// The most draws in a single game, every game has at least one.
+    pub draws: usize,
+    /// How many of [`COLOURS`] are in the bag.
+    pub colours: usize,
+    /// The most cubes of a colour in the bag or in a draw.
+    pub max_count: u32,
+    pub seed: u64,
+}
+
+impl Default for Config {
+    /// Roughly the size and shape of the puzzle input.
+    fn default() -> Self {
+        Config {
+            games: 100,
+            draws: 6,
+            colours: 3,
+            max_count: 20,
+            seed: 0,
+        }
+    }
+}
+
+/// The bag a generated input is played with and both of its answers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Generated {
+    pub bag: CubeSet,
+    /// The sum of the ids of the possible games.
+    pub possible: u64,
+    /// The sum of the powers of the games.
+    pub power: u64,
+}
+
+/// Writes an input of `config.games` games numbered from 1 to `writer` a game at a time, so inputs
+/// of any size can be made without holding them in memory. The same config always makes the same
+/// input.
+///
+/// The answers are worked out from the counts as they are generated rather than from the parsed
+/// games, so they make a check on the parser and the solutions that doesn't share any of their
+/// code. Counts are at least 1 for draws, while the bag can hold fewer of a colour than some draws
+/// so that not every game is possible.
+pub fn generate<W: Write>(config: &Config, mut writer: W) -> Result<Generated> {
+    if config.colours == 0 || config.colours > COLOURS.len() {
+        return Err(Error::unsupported(format!(
+            "between 1 and {} colours can be generated, not {}",
+            COLOURS.len(),
+            config.colours
+        )));
+    }
+    if config.draws == 0 || config.max_count == 0 {
+        return Err(Error::unsupported(
+            "games need at least one draw of at least one cube",
+        ));
+    }
+
+    let mut rng = Rng::new(config.seed);
+    let max_count = u64::from(config.max_count);
+    let bag: Vec<u32> = (0..config.colours)
+        .map(|_| rng.between(max_count.div_ceil(2), max_count) as u32)
+        .collect();
+
+    let mut possible: u64 = 0;
+    let mut power: u64 = 0;
+    let mut order: Vec<usize> = (0..config.colours).collect();
+    for id in 1..=config.games {
+        let mut maxima = vec![0; config.colours];
+        let mut draws: Vec<String> = vec![];
+
+        for _ in 0..rng.between(1, config.draws as u64) {
+            rng.shuffle(&mut order);
+            let shown = rng.between(1, config.colours as u64) as usize;
+            let cubes: Vec<String> = order[..shown]
+                .iter()
+                .map(|colour| {
+                    let count = rng.between(1, max_count) as u32;
+                    maxima[*colour] = maxima[*colour].max(count);
+                    format!("{} {}", count, COLOURS[*colour])
+                })
+                .collect();
+            draws.push(cubes.join(", "));
+        }
+        writeln!(writer, "Game {}: {}", id, draws.join("; "))?;
+
+        if maxima.iter().zip(&bag).all(|(max, held)| max <= held) {
+            possible += id as u64;
+        }
+        let product = maxima
+            .iter()
+            .try_fold(1u64, |product, max| product.checked_mul(u64::from(*max)))
+            .ok_or_else(|| {
+                Error::overflow(format!("the power of game {} doesn't fit in a u64", id))
+            })?;
+        power = power
+            .checked_add(product)
+            .ok_or_else(|| Error::overflow("the power sum doesn't fit in a u64"))?;
+    }
+    writer.flush()?;
+
+    Ok(Generated {
+        bag: COLOURS.iter().copied().zip(bag).collect(),
+        possible,
+        power,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate, Config, Generated};
+    use crate::day_02::{line_powers_total, line_values_total, parse_games, totals, Totals};
+    use std::io;
+
+    fn test_generate_case(config: &Config) -> Result<(), String> {
+        let mut input: Vec<u8> = vec![];
+        let generated = generate(config, &mut input).map_err(|err| err.to_string())?;
+        let input = String::from_utf8(input).map_err(|err| err.to_string())?;
+        let games =
+            parse_games(&input, &generated.bag).map_err(|err| format!("{:?}: {}", config, err))?;
+
+        let got = (
+            games.len(),
+            line_values_total(&games, &generated.bag).map_err(|err| err.to_string())?,
+            line_powers_total(&games, &generated.bag).map_err(|err| err.to_string())?,
+        );
+        let want = (config.games, generated.possible, generated.power);
+        if got != want {
+            return Err(format!("{:?}: want {:?}, got {:?}", config, want, got));
+        }
+
+        let streamed = totals(input.as_bytes(), &generated.bag).map_err(|err| err.to_string())?;
+        let want = Totals {
+            games: config.games,
+            possible: generated.possible,
+            power: generated.power,
+        };
+        if streamed != want {
+            return Err(format!("{:?}: want {:?}, got {:?}", config, want, streamed));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate() -> Result<(), String> {
+        let default = Config::default();
+        (0..20)
+            .map(|seed| Config { seed, ..default })
+            .chain([
+                Config {
+                    games: 10_000,
+                    ..default
+                },
+                Config {
+                    colours: 1,
+                    draws: 1,
+                    max_count: 1,
+                    ..default
+                },
+                Config {
+                    colours: 8,
+                    draws: 20,
+                    max_count: 100,
+                    ..default
+                },
+                Config {
+                    games: 0,
+                    ..default
+                },
+            ])
+            .try_for_each(|config| test_generate_case(&config))?;
+
+        Ok(())
+    }
+
+    /// The input and answers generated for `config`.
+    fn generate_input(config: &Config) -> (Vec<u8>, Generated) {
+        let mut input: Vec<u8> = vec![];
+        let generated = generate(config, &mut input).unwrap();
+        (input, generated)
+    }
+
+    #[test]
+    fn test_generate_is_seeded() {
+        let (input, generated) = generate_input(&Config::default());
+
+        assert_eq!(
+            (input.clone(), generated.clone()),
+            generate_input(&Config::default())
+        );
+        assert_ne!(
+            input,
+            generate_input(&Config {
+                seed: 1,
+                ..Config::default()
+            })
+            .0
+        );
+
+        // Some games should be possible and some not for the answers to mean anything.
+        let possible = generated.possible;
+        assert!(possible > 0 && possible < (1..=100).sum(), "{}", possible);
+    }
+
+    #[test]
+    fn test_generate_errors() {
+        let default = Config::default();
+        [
+            (
+                Config {
+                    colours: 9,
+                    ..default
+                },
+                "unsupported: between 1 and 8 colours can be generated, not 9",
+            ),
+            (
+                Config {
+                    draws: 0,
+                    ..default
+                },
+                "unsupported: games need at least one draw of at least one cube",
+            ),
+            (
+                Config {
+                    colours: 8,
+                    max_count: u32::MAX,
+                    ..default
+                },
+                "overflow: the power of game 3 doesn't fit in a u64",
+            ),
+            // Two colours always fit in a u64 but a hundred games of them don't.
+            (
+                Config {
+                    colours: 2,
+                    max_count: u32::MAX,
+                    ..default
+                },
+                "overflow: the power sum doesn't fit in a u64",
+            ),
+        ]
+        .iter()
+        .for_each(|(config, want)| {
+            assert_eq!(*want, generate(config, io::sink()).unwrap_err().to_string());
+        });
+    }
+}
@@ -0,0 +1,265 @@
+//! What the games say about the bag they were played with, see [`infer_bag`].
+
+use crate::day_02::{CubeSet, Game};
+use serde::Serialize;
+
+/// The z score of a 95% confidence interval.
+const Z_95: f64 = 1.96;
+
+/// The bag as far as a set of games can tell.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BagEstimate {
+    pub games: usize,
+    pub draws: usize,
+    /// Every cube shown across all the draws.
+    pub cubes: u64,
+    /// The size of the most likely bag, the smallest that keeps every colour's share and still
+    /// holds every draw.
+    pub size: u64,
+    pub colours: Vec<ColourEstimate>,
+}
+
+/// What the draws say about a single colour.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ColourEstimate {
+    pub colour: String,
+    /// The most shown in a single draw, the bag can't hold any fewer.
+    pub minimum: u32,
+    /// Every cube of this colour shown across all the draws.
+    pub seen: u64,
+    /// The maximum likelihood share of the bag, what was seen of this colour over every cube seen.
+    pub share: f64,
+    /// The 95% Wilson score interval of the share.
+    pub share_low: f64,
+    pub share_high: f64,
+    /// The count in the most likely bag, never less than the minimum.
+    pub estimate: u64,
+}
+
+impl BagEstimate {
+    /// The smallest bag every draw could have come out of.
+    pub fn minimum_bag(&self) -> CubeSet {
+        self.colours
+            .iter()
+            .map(|colour| (colour.colour.as_str(), colour.minimum))
+            .collect()
+    }
+}
+
+/// Estimates the bag behind `games` for each of the colours in `bag`.
+///
+/// The minimum is the largest count shown in any one draw. The most likely bag treats every cube
+/// shown as an independent pick from the bag, since each handful is put back before the next,
+/// so a colour's maximum likelihood share is simply how often it was seen. Shares alone don't
+/// say how big the bag is, so they are scaled to the smallest bag that still holds the minimum of
+/// every colour.
+pub fn infer_bag(games: &[Game], bag: &CubeSet) -> BagEstimate {
+    let draws = games.iter().flat_map(|game| &game.draws);
+    let minimum = games.iter().fold(CubeSet::new(), |minimum, game| {
+        minimum.union(&game.minimum_set())
+    });
+
+    let mut seen_totals: Vec<(String, u64)> = bag
+        .colours()
+        .iter()
+        .map(|colour| (String::from(*colour), 0))
+        .collect();
+    for draw in draws.clone() {
+        for (colour, count) in draw.iter() {
+            if let Some((_, total)) = seen_totals.iter_mut().find(|(c, _)| c == colour) {
+                *total += u64::from(count);
+            }
+        }
+    }
+    let cubes: u64 = seen_totals.iter().map(|(_, total)| total).sum();
+
+    let share = |seen: u64| match cubes {
+        0 => 0.0,
+        _ => seen as f64 / cubes as f64,
+    };
+    let size = seen_totals
+        .iter()
+        .filter(|(_, seen)| *seen > 0)
+        .map(|(colour, seen)| (f64::from(minimum.get(colour)) / share(*seen)).ceil() as u64)
+        .max()
+        .unwrap_or_default();
+
+    let colours = seen_totals
+        .into_iter()
+        .map(|(colour, seen)| {
+            let (share_low, share_high) = wilson_interval(seen, cubes);
+            let minimum = minimum.get(&colour);
+            ColourEstimate {
+                estimate: ((share(seen) * size as f64).round() as u64).max(u64::from(minimum)),
+                colour,
+                minimum,
+                seen,
+                share: share(seen),
+                share_low,
+                share_high,
+            }
+        })
+        .collect();
+
+    BagEstimate {
+        games: games.len(),
+        draws: draws.count(),
+        cubes,
+        size,
+        colours,
+    }
+}
+
+/// The 95% Wilson score interval for `successes` out of `trials`, which unlike the normal
+/// approximation stays within 0 and 1 for small counts and shares near either end.
+fn wilson_interval(successes: u64, trials: u64) -> (f64, f64) {
+    if trials == 0 {
+        return (0.0, 1.0);
+    }
+
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let z2 = Z_95 * Z_95;
+    let centre = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half = Z_95 * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+
+    ((centre - half).max(0.0), (centre + half).min(1.0))
+}
+
+/// One row per colour followed by the minimum and most likely bags.
+pub fn to_table(estimate: &BagEstimate) -> String {
+    let mut table = format!(
+        "{} games, {} draws, {} cubes\n\n{:<10}{:>8}{:>8}{:>8}{:>17}{:>10}\n",
+        estimate.games,
+        estimate.draws,
+        estimate.cubes,
+        "colour",
+        "minimum",
+        "seen",
+        "share",
+        "95% interval",
+        "estimate"
+    );
+    for colour in &estimate.colours {
+        table.push_str(&format!(
+            "{:<10}{:>8}{:>8}{:>8.3}{:>17}{:>10}\n",
+            colour.colour,
+            colour.minimum,
+            colour.seen,
+            colour.share,
+            format!("{:.3} - {:.3}", colour.share_low, colour.share_high),
+            colour.estimate,
+        ));
+    }
+
+    let bag = |count: &dyn Fn(&ColourEstimate) -> String| {
+        estimate
+            .colours
+            .iter()
+            .map(|colour| format!("{} {}", count(colour), colour.colour))
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+    table.push_str(&format!(
+        "\nminimum bag: {}\nmost likely bag: {}\n",
+        bag(&|colour| colour.minimum.to_string()),
+        bag(&|colour| colour.estimate.to_string()),
+    ));
+    table
+}
+
+pub fn to_json(estimate: &BagEstimate) -> String {
+    // Only strings and finite numbers are held so serialising can't fail.
+    serde_json::to_string_pretty(estimate).unwrap_or_default() + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{infer_bag, to_table, wilson_interval};
+    use crate::day_02::{default_bag, parse_bag, parse_games, Day02};
+    use crate::solution::Solution;
+
+    fn assert_close(want: f64, got: f64) {
+        assert!((want - got).abs() < 1e-4, "want {}, got {}", want, got);
+    }
+
+    #[test]
+    fn test_wilson_interval() {
+        let (low, high) = wilson_interval(3, 6);
+        assert_close(0.1876, low);
+        assert_close(0.8124, high);
+
+        assert_eq!((0.0, 1.0), wilson_interval(0, 0));
+        let (low, high) = wilson_interval(0, 10);
+        assert_eq!(0.0, low);
+        assert!(high > 0.0 && high < 0.5);
+    }
+
+    #[test]
+    fn test_infer_bag() {
+        let bag = parse_bag("1 red, 1 blue, 1 green").unwrap();
+        let games = parse_games("Game 1: 2 red, 2 blue; 1 red, 1 blue", &bag).unwrap();
+        let estimate = infer_bag(&games, &bag);
+
+        assert_eq!(
+            (1, 2, 6, 4),
+            (
+                estimate.games,
+                estimate.draws,
+                estimate.cubes,
+                estimate.size
+            )
+        );
+        assert_eq!(
+            parse_bag("2 blue, 0 green, 2 red").unwrap(),
+            estimate.minimum_bag()
+        );
+
+        let blue = &estimate.colours[0];
+        assert_eq!(
+            ("blue", 2, 3, 2),
+            (blue.colour.as_str(), blue.minimum, blue.seen, blue.estimate)
+        );
+        assert_close(0.5, blue.share);
+        assert_close(0.1876, blue.share_low);
+
+        // A colour that never came out still gets a row, with nothing known about it.
+        let green = &estimate.colours[1];
+        assert_eq!(
+            ("green", 0, 0, 0),
+            (
+                green.colour.as_str(),
+                green.minimum,
+                green.seen,
+                green.estimate
+            )
+        );
+    }
+
+    #[test]
+    fn test_infer_example() {
+        let games = parse_games(Day02::EXAMPLE, &default_bag()).unwrap();
+        let estimate = infer_bag(&games, &default_bag());
+
+        assert_eq!(
+            parse_bag("20 red, 13 green, 15 blue").unwrap(),
+            estimate.minimum_bag()
+        );
+        assert_close(
+            1.0,
+            estimate.colours.iter().map(|colour| colour.share).sum(),
+        );
+        for colour in &estimate.colours {
+            assert!(colour.share_low <= colour.share && colour.share <= colour.share_high);
+            assert!(colour.estimate >= u64::from(colour.minimum));
+        }
+
+        let table = to_table(&estimate);
+        assert!(table.starts_with("5 games, 14 draws, "), "{}", table);
+        assert!(
+            table.contains("minimum bag: 15 blue, 13 green, 20 red\n"),
+            "{}",
+            table
+        );
+    }
+}
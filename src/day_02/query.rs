@@ -0,0 +1,521 @@
+//! A small expression language for picking out day 2 games, see [`parse_query`] and [`query`].
+//!
+//! ```text
+//! expression := or
+//! or         := and ("or" and)*
+//! and        := not ("and" not)*
+//! not        := "not" not | "(" expression ")" | comparison
+//! comparison := value ("<" | "<=" | ">" | ">=" | "==" | "!=") value
+//! value      := number | "id" | "draws" | "power" | colour | ("min" | "max" | "sum") "(" colour ")"
+//! ```
+//!
+//! A colour on its own is the most of it shown in a single draw, the same as `max(colour)`, and
+//! `power` is the power of the game's minimum bag. A colour missing from a draw counts as zero.
+
+use crate::day_02::{CubeSet, Game};
+use crate::error::{Error, Result};
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Value, Op, Value),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Number(u64),
+    Id,
+    Draws,
+    Power,
+    /// A colour counted across the draws of a game.
+    Colour(Aggregate, String),
+}
+
+/// How the counts of a colour in each draw are combined into one value for the game.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Aggregate {
+    Min,
+    Max,
+    Sum,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Op::Lt => write!(f, "<"),
+            Op::Le => write!(f, "<="),
+            Op::Gt => write!(f, ">"),
+            Op::Ge => write!(f, ">="),
+            Op::Eq => write!(f, "=="),
+            Op::Ne => write!(f, "!="),
+        }
+    }
+}
+
+impl Expr {
+    /// Whether `game` matches, `bag` gives the colours that make up the power. Fails when a
+    /// value it compares doesn't fit in a u64.
+    pub fn matches(&self, game: &Game, bag: &CubeSet) -> Result<bool> {
+        Ok(match self {
+            Expr::Or(left, right) => left.matches(game, bag)? || right.matches(game, bag)?,
+            Expr::And(left, right) => left.matches(game, bag)? && right.matches(game, bag)?,
+            Expr::Not(expr) => !expr.matches(game, bag)?,
+            Expr::Compare(left, op, right) => {
+                let (left, right) = (left.evaluate(game, bag)?, right.evaluate(game, bag)?);
+                match op {
+                    Op::Lt => left < right,
+                    Op::Le => left <= right,
+                    Op::Gt => left > right,
+                    Op::Ge => left >= right,
+                    Op::Eq => left == right,
+                    Op::Ne => left != right,
+                }
+            }
+        })
+    }
+}
+
+impl Value {
+    pub fn evaluate(&self, game: &Game, bag: &CubeSet) -> Result<u64> {
+        Ok(match self {
+            Value::Number(n) => *n,
+            Value::Id => u64::from(game.id),
+            Value::Draws => game.draws.len() as u64,
+            Value::Power => game.minimum_set().power(&bag.colours())?,
+            Value::Colour(aggregate, colour) => {
+                let counts = game.draws.iter().map(|draw| u64::from(draw.get(colour)));
+                match aggregate {
+                    Aggregate::Min => counts.min().unwrap_or_default(),
+                    Aggregate::Max => counts.max().unwrap_or_default(),
+                    Aggregate::Sum => counts.sum(),
+                }
+            }
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Number(u64),
+    Op(Op),
+    Open,
+    Close,
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "'{}'", word),
+            Token::Number(n) => write!(f, "'{}'", n),
+            Token::Op(op) => write!(f, "'{}'", op),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+            Token::End => write!(f, "the end of the query"),
+        }
+    }
+}
+
+/// Splits the query into tokens along with the column each starts at.
+fn tokenise(text: &str) -> Result<Vec<(Token, usize)>> {
+    let mut tokens: Vec<(Token, usize)> = vec![];
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((offset, c)) = chars.next() {
+        let column = text[..offset].chars().count() + 1;
+        let mut take_while = |first: char, predicate: fn(&char) -> bool| {
+            let mut word = String::from(first);
+            while let Some((_, c)) = chars.next_if(|(_, c)| predicate(c)) {
+                word.push(c);
+            }
+            word
+        };
+
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '<' | '>' | '=' | '!' => {
+                let op = take_while(c, |c| *c == '=');
+                Token::Op(match op.as_str() {
+                    "<" => Op::Lt,
+                    "<=" => Op::Le,
+                    ">" => Op::Gt,
+                    ">=" => Op::Ge,
+                    "==" => Op::Eq,
+                    "!=" => Op::Ne,
+                    _ => {
+                        return Err(
+                            Error::parse(format!("unknown operator '{}'", op)).at_column(column)
+                        )
+                    }
+                })
+            }
+            c if c.is_ascii_digit() => {
+                let digits = take_while(c, char::is_ascii_digit);
+                Token::Number(digits.parse::<u64>().map_err(|err| {
+                    Error::parse(format!("invalid number '{}': {}", digits, err)).at_column(column)
+                })?)
+            }
+            c if c.is_alphabetic() => {
+                Token::Word(take_while(c, |c| c.is_alphanumeric() || *c == '_'))
+            }
+            _ => {
+                return Err(Error::parse(format!("unexpected '{}'", c)).at_column(column));
+            }
+        };
+        tokens.push((token, column));
+    }
+
+    tokens.push((Token::End, text.chars().count() + 1));
+    Ok(tokens)
+}
+
+/// Parses a query, any colour it names has to be in `bag`.
+pub fn parse_query(text: &str, bag: &CubeSet) -> Result<Expr> {
+    let mut parser = Parser {
+        tokens: tokenise(text)?,
+        position: 0,
+        bag,
+    };
+
+    let expr = parser.or()?;
+    match parser.peek() {
+        Token::End => Ok(expr),
+        _ => Err(parser.error("'and', 'or' or the end of the query")),
+    }
+}
+
+/// A recursive descent parser over the tokens, one method per rule of the grammar.
+struct Parser<'a> {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    bag: &'a CubeSet,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position].0
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.position].0.clone();
+        // The end token is never moved past so there is always something to peek at.
+        if token != Token::End {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn is_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Token::Word(w) if w == word)
+    }
+
+    fn error(&self, expected: &str) -> Error {
+        let (token, column) = &self.tokens[self.position];
+        Error::parse(format!("expected {}, found {}", expected, token)).at_column(*column)
+    }
+
+    fn expect(&mut self, token: Token, expected: &str) -> Result<()> {
+        if *self.peek() == token {
+            self.next();
+            Ok(())
+        } else {
+            Err(self.error(expected))
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+        while self.is_word("or") {
+            self.next();
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.not()?;
+        while self.is_word("and") {
+            self.next();
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        if self.is_word("not") {
+            self.next();
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        if *self.peek() == Token::Open {
+            self.next();
+            let expr = self.or()?;
+            self.expect(Token::Close, "')'")?;
+            return Ok(expr);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr> {
+        let left = self.value()?;
+        let op = match self.peek() {
+            Token::Op(op) => *op,
+            _ => return Err(self.error("a comparison")),
+        };
+        self.next();
+        let right = self.value()?;
+
+        Ok(Expr::Compare(left, op, right))
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        let aggregate = match self.peek() {
+            Token::Number(n) => {
+                let n = *n;
+                self.next();
+                return Ok(Value::Number(n));
+            }
+            Token::Word(word) => match word.as_str() {
+                "id" | "draws" | "power" => {
+                    let value = match word.as_str() {
+                        "id" => Value::Id,
+                        "draws" => Value::Draws,
+                        _ => Value::Power,
+                    };
+                    self.next();
+                    return Ok(value);
+                }
+                "min" => Some(Aggregate::Min),
+                "max" => Some(Aggregate::Max),
+                "sum" => Some(Aggregate::Sum),
+                _ => None,
+            },
+            _ => return Err(self.error("a number, 'id', 'draws', 'power' or a colour")),
+        };
+
+        match aggregate {
+            Some(aggregate) => {
+                self.next();
+                self.expect(Token::Open, "'('")?;
+                let colour = self.colour()?;
+                self.expect(Token::Close, "')'")?;
+                Ok(Value::Colour(aggregate, colour))
+            }
+            None => Ok(Value::Colour(Aggregate::Max, self.colour()?)),
+        }
+    }
+
+    fn colour(&mut self) -> Result<String> {
+        match self.peek() {
+            Token::Word(word) if self.bag.contains(word) => {
+                let colour = word.clone();
+                self.next();
+                Ok(colour)
+            }
+            Token::Word(word) => {
+                let column = self.tokens[self.position].1;
+                Err(Error::parse(format!(
+                    "unknown colour '{}', the bag holds {}",
+                    word,
+                    self.bag.colours().join(", ")
+                ))
+                .at_column(column))
+            }
+            _ => Err(self.error("a colour")),
+        }
+    }
+}
+
+/// The games that matched a query and some totals over them.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct QueryResult {
+    pub ids: Vec<u32>,
+    /// How many games were searched.
+    pub games: usize,
+    pub id_sum: u64,
+    pub power_sum: u64,
+}
+
+/// The games matching `expr`, failing with an overflow error when a power or the sum of the
+/// powers doesn't fit in a u64.
+pub fn query(games: &[Game], bag: &CubeSet, expr: &Expr) -> Result<QueryResult> {
+    let colours = bag.colours();
+    let mut result = QueryResult {
+        games: games.len(),
+        ..QueryResult::default()
+    };
+
+    for game in games {
+        if !expr.matches(game, bag)? {
+            continue;
+        }
+        result.ids.push(game.id);
+        result.id_sum += u64::from(game.id);
+        result.power_sum = result
+            .power_sum
+            .checked_add(game.minimum_set().power(&colours)?)
+            .ok_or_else(|| Error::overflow("the power sum doesn't fit in a u64"))?;
+    }
+
+    Ok(result)
+}
+
+pub fn to_text(result: &QueryResult) -> String {
+    let ids: Vec<String> = result.ids.iter().map(|id| id.to_string()).collect();
+    format!(
+        "games: {}\nmatched {} of {} games, id sum {}, power sum {}\n",
+        if ids.is_empty() {
+            String::from("-")
+        } else {
+            ids.join(", ")
+        },
+        result.ids.len(),
+        result.games,
+        result.id_sum,
+        result.power_sum,
+    )
+}
+
+pub fn to_json(result: &QueryResult) -> String {
+    // Only numbers are held so serialising can't fail.
+    serde_json::to_string_pretty(result).unwrap_or_default() + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_query, query, to_text, Aggregate, Expr, Op, Value};
+    use crate::day_02::{default_bag, parse_bag, parse_games, Day02};
+    use crate::solution::Solution;
+
+    fn test_query_case(text: &str, want: &[u32]) -> Result<(), String> {
+        let games = parse_games(Day02::EXAMPLE, &default_bag()).map_err(|err| err.to_string())?;
+        let expr = parse_query(text, &default_bag()).map_err(|err| err.to_string())?;
+
+        let got = query(&games, &default_bag(), &expr)
+            .map_err(|err| err.to_string())?
+            .ids;
+        if got != want {
+            Err(format!("{}: want {:?}, got {:?}", text, want, got))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_query() -> Result<(), String> {
+        [
+            ("max(red) > 10", vec![3, 4]),
+            ("red > 10", vec![3, 4]),
+            ("power > 500", vec![3, 4]),
+            ("power >= 36 and power <= 630", vec![1, 4, 5]),
+            ("id == 1 or id == 5", vec![1, 5]),
+            ("not (red > 12 or green > 13 or blue > 14)", vec![1, 2, 5]),
+            ("sum(blue) >= 10 and not draws == 2", vec![3, 4]),
+            ("min(green) == 0", vec![1]),
+            ("min(green) > 0", vec![2, 3, 4, 5]),
+            ("red > 10 or green > 3 and draws == 2", vec![3, 4]),
+            ("(red > 10 or green > 3) and draws == 2", vec![]),
+            ("1 < 2", vec![1, 2, 3, 4, 5]),
+        ]
+        .iter()
+        .try_for_each(|(text, want)| test_query_case(text, want))?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let bag = default_bag();
+
+        assert_eq!(
+            Expr::Or(
+                Box::new(Expr::Compare(
+                    Value::Colour(Aggregate::Sum, String::from("red")),
+                    Op::Ge,
+                    Value::Number(3),
+                )),
+                Box::new(Expr::Not(Box::new(Expr::Compare(
+                    Value::Id,
+                    Op::Ne,
+                    Value::Power,
+                )))),
+            ),
+            parse_query("sum(red)>=3 or not id != power", &bag).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_query_errors() {
+        let bag = default_bag();
+
+        [
+            ("", "column 1: parse error: expected a number, 'id', 'draws', 'power' or a colour, found the end of the query"),
+            ("red", "column 4: parse error: expected a comparison, found the end of the query"),
+            ("red > 1 blue > 2", "column 9: parse error: expected 'and', 'or' or the end of the query, found 'blue'"),
+            ("purple > 1", "column 1: parse error: unknown colour 'purple', the bag holds blue, green, red"),
+            ("max(red > 1", "column 9: parse error: expected ')', found '>'"),
+            ("(red > 1", "column 9: parse error: expected ')', found the end of the query"),
+            ("red => 1", "column 5: parse error: unknown operator '='"),
+            ("red > 1 & blue > 1", "column 9: parse error: unexpected '&'"),
+            ("sum red > 1", "column 5: parse error: expected '(', found 'red'"),
+        ]
+        .iter()
+        .for_each(|(text, want)| {
+            assert_eq!(*want, parse_query(text, &bag).unwrap_err().to_string(), "{}", text)
+        });
+    }
+
+    #[test]
+    fn test_to_text() {
+        let games = parse_games(Day02::EXAMPLE, &default_bag()).unwrap();
+        let expr = parse_query("power > 500", &default_bag()).unwrap();
+
+        assert_eq!(
+            "games: 3, 4\nmatched 2 of 5 games, id sum 7, power sum 2190\n",
+            to_text(&query(&games, &default_bag(), &expr).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_query_overflow() {
+        let bag = parse_bag("1 red, 1 green").unwrap();
+        let games = parse_games(
+            "Game 1: 4294967295 red, 4294967295 green\nGame 2: 4294967295 red, 4294967295 green",
+            &bag,
+        )
+        .unwrap();
+
+        // Each power fits, so comparing them is fine but summing them isn't.
+        let expr = parse_query("power > 5", &bag).unwrap();
+        assert_eq!(
+            "overflow: the power sum doesn't fit in a u64",
+            query(&games, &bag, &expr).unwrap_err().to_string()
+        );
+        let expr = parse_query("id == 2 and power > 5", &bag).unwrap();
+        assert_eq!(vec![2], query(&games, &bag, &expr).unwrap().ids);
+
+        let bag = parse_bag("1 red, 1 green, 1 blue").unwrap();
+        let games = parse_games("Game 1: 4294967295 red, 4294967295 green, 2 blue", &bag).unwrap();
+        let expr = parse_query("power > 5", &bag).unwrap();
+        assert_eq!(
+            "overflow: the power doesn't fit in a u64",
+            query(&games, &bag, &expr).unwrap_err().to_string()
+        );
+    }
+}
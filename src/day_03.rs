@@ -1,8 +1,10 @@
+//! Day 3: Gear Ratios. Find the part numbers and gears in an engine schematic.
+
+use crate::error::{Error, Result};
+use crate::solution::{Options, Solution};
 use num::ToPrimitive;
 use std::collections::HashSet;
-use std::error::Error;
 use std::io::{BufRead, BufReader, Cursor};
-use std::{env, fs};
 
 #[derive(Clone, PartialEq, Debug)]
 enum CellType {
@@ -12,8 +14,9 @@ enum CellType {
     Gear,
 }
 
+/// A single character of the schematic.
 #[derive(Clone, Debug)]
-struct Cell {
+pub struct Cell {
     cell_value: usize,
     cell_type: CellType,
 }
@@ -45,19 +48,51 @@ impl Cell {
     }
 }
 
-pub fn totals() -> Result<(usize, usize), Box<dyn Error>> {
-    let file_path = format!(
-        "{}/data/input_day_03.txt",
-        env::current_dir().unwrap().display()
-    );
-    Ok((
-        sum_part_numbers(fs::read_to_string(&file_path)?)?,
-        sum_gear_ratios(fs::read_to_string(&file_path)?)?,
-    ))
+/// The rows of the schematic with a border of periods on every side, see
+/// [`create_padded_schematic`].
+#[derive(Clone, Debug)]
+pub struct Schematic {
+    rows: Vec<Vec<Cell>>,
+    /// The line of the input each row came from, 0 for the padding rows.
+    lines: Vec<usize>,
 }
 
-fn sum_part_numbers(input: String) -> Result<usize, Box<dyn Error>> {
-    let schem = create_padded_schematic(input)?;
+/// Part one sums the part numbers, part two sums the gear ratios.
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+    const EXAMPLE: &'static str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+";
+
+    type Input = Schematic;
+    type Answer = usize;
+
+    fn parse(input: &str, _: &Options) -> Result<Self::Input> {
+        create_padded_schematic(input)
+    }
+
+    fn part_one(schem: &Self::Input) -> Result<Self::Answer> {
+        sum_part_numbers(schem)
+    }
+
+    fn part_two(schem: &Self::Input) -> Result<Self::Answer> {
+        sum_gear_ratios(schem)
+    }
+}
+
+/// The sum of every number adjacent to a symbol, including diagonally.
+pub fn sum_part_numbers(schematic: &Schematic) -> Result<usize> {
+    let schem = &schematic.rows;
     let mut sum: usize = 0;
 
     for (row_index, row) in schem.iter().enumerate() {
@@ -131,7 +166,18 @@ fn sum_part_numbers(input: String) -> Result<usize, Box<dyn Error>> {
                 // convert back to usize :facepalm: Must be a nicer way than this...
                 let string_vals: Vec<String> = digits.iter().map(|&n| n.to_string()).collect();
                 let concat_string: String = string_vals.join("");
-                sum += concat_string.parse::<usize>()?;
+                // The padding column means indexes are already one based.
+                let line = schematic.lines[row_index];
+                let part_number = concat_string.parse::<usize>().map_err(|err| {
+                    Error::parse(format!("invalid part number '{}': {}", concat_string, err))
+                        .at_line(line)
+                        .at_column(cell_index - scan_width)
+                })?;
+                sum = sum.checked_add(part_number).ok_or_else(|| {
+                    Error::overflow("the sum of the part numbers doesn't fit in a usize")
+                        .at_line(line)
+                        .at_column(cell_index - scan_width)
+                })?;
             }
 
             digits.clear();
@@ -141,8 +187,9 @@ fn sum_part_numbers(input: String) -> Result<usize, Box<dyn Error>> {
     Ok(sum)
 }
 
-fn sum_gear_ratios(input: String) -> Result<usize, Box<dyn Error>> {
-    let schem = create_padded_schematic(input)?;
+/// The sum of the products of the two part numbers next to each `*` that has exactly two.
+pub fn sum_gear_ratios(schematic: &Schematic) -> Result<usize> {
+    let schem = &schematic.rows;
     let mut gear_ratios: Vec<usize> = vec![];
 
     for (row_index, row) in schem.iter().enumerate() {
@@ -153,21 +200,26 @@ fn sum_gear_ratios(input: String) -> Result<usize, Box<dyn Error>> {
 
         for (cell_index, cell) in row.iter().enumerate() {
             // Skip first cell as this is just padding and anything that's not a gear.
-            if cell_index == 0 {
-                continue;
-            } else if !cell.is_gear() {
+            if cell_index == 0 || !cell.is_gear() {
                 continue;
             }
 
             let mut coordinates = get_adjacent_coordinates(row_index, cell_index);
 
-            if let Some(gear_ratio) = determine_gear_ratio(&schem, &mut coordinates) {
-                gear_ratios.push(gear_ratio)
-            }
+            // The padding column means indexes are already one based.
+            let gear_ratio = determine_gear_ratio(schematic, &mut coordinates).map_err(|err| {
+                err.at_line(schematic.lines[row_index])
+                    .at_column(cell_index)
+            })?;
+            gear_ratios.push(gear_ratio);
         }
     }
 
-    Ok(gear_ratios.iter().sum())
+    gear_ratios.iter().try_fold(0usize, |total, gear_ratio| {
+        total
+            .checked_add(*gear_ratio)
+            .ok_or_else(|| Error::overflow("the sum of the gear ratios doesn't fit in a usize"))
+    })
 }
 
 fn get_adjacent_coordinates(row: usize, col: usize) -> HashSet<Coordinate> {
@@ -211,10 +263,14 @@ fn get_adjacent_coordinates(row: usize, col: usize) -> HashSet<Coordinate> {
     coordinates
 }
 
+/// The product of the two part numbers next to a gear, or 0 when it doesn't have exactly two.
+/// Fails when a part number or the product doesn't fit in a usize, errors from the part numbers
+/// carry their own line and column.
 fn determine_gear_ratio(
-    schem: &[Vec<Cell>],
+    schematic: &Schematic,
     coordinates: &mut HashSet<Coordinate>,
-) -> Option<usize> {
+) -> Result<usize> {
+    let schem = &schematic.rows;
     let mut part_nums: Vec<usize> = vec![];
     let mut scanned: HashSet<Coordinate> = HashSet::new();
 
@@ -237,6 +293,8 @@ fn determine_gear_ratio(
             }
             pos -= 1;
         }
+        pos += 1;
+        let start = pos;
 
         // We now have the starting column, walk right until we determine the entire part number.
         loop {
@@ -253,7 +311,11 @@ fn determine_gear_ratio(
         }
 
         if !part_num.is_empty() {
-            part_nums.push(part_num.parse::<usize>().ok()?);
+            part_nums.push(part_num.parse::<usize>().map_err(|err| {
+                Error::parse(format!("invalid part number '{}': {}", part_num, err))
+                    .at_line(schematic.lines[coordinate.x])
+                    .at_column(start)
+            })?);
         }
     }
 
@@ -261,33 +323,45 @@ fn determine_gear_ratio(
     let mut result: usize = 0;
     if part_nums.len() == 2 {
         // The gear ratio is the result of multiplying the part numbers together.
-        result = part_nums.iter().fold(1, |acc, &num| acc * num);
+        result = part_nums[0]
+            .checked_mul(part_nums[1])
+            .ok_or_else(|| Error::overflow("the gear ratio doesn't fit in a usize"))?;
     }
-    Some(result)
+    Ok(result)
 }
 
-fn create_padded_schematic(input: String) -> Result<Vec<Vec<Cell>>, Box<dyn Error>> {
+/// Parses the schematic into rows of cells with a border of periods on every side, skipping blank
+/// lines.
+pub fn create_padded_schematic(input: &str) -> Result<Schematic> {
     let cursor = Cursor::new(input);
     let reader = BufReader::new(cursor);
 
     let mut schematic: Vec<Vec<Cell>> = Vec::new();
+    let mut lines: Vec<usize> = vec![0];
     let mut line_len: usize = 0;
 
     for (i, line) in reader.lines().enumerate() {
-        let line = line.unwrap_or_default();
+        let line = line?;
 
         // Skip empty lines.
         if line.is_empty() {
             continue;
         }
 
-        // We only need to set the length once.
-        if i == 0 {
-            line_len = line.len().to_usize().unwrap();
+        // We only need to set the length once, every other row has to match it.
+        let width = line.chars().count();
+        if schematic.is_empty() {
+            line_len = width;
+        } else if width != line_len {
+            return Err(Error::malformed(format!(
+                "expected {} columns, found {}",
+                line_len, width
+            ))
+            .at_line(i + 1));
         }
 
         let mut row: Vec<Cell> = Vec::new();
-        for char in line.chars() {
+        for (col, char) in line.chars().enumerate() {
             let (cell_value, cell_type): (usize, CellType);
             match char {
                 '.' => {
@@ -297,9 +371,12 @@ fn create_padded_schematic(input: String) -> Result<Vec<Vec<Cell>>, Box<dyn Erro
                 '0'..='9' => {
                     cell_value = char
                         .to_digit(10)
-                        .ok_or("invalid digit")?
-                        .to_usize()
-                        .ok_or("invalid usize")?;
+                        .and_then(|digit| digit.to_usize())
+                        .ok_or_else(|| {
+                            Error::parse(format!("invalid digit '{}'", char))
+                                .at_line(i + 1)
+                                .at_column(col + 1)
+                        })?;
                     cell_type = CellType::Digit;
                 }
                 '*' => {
@@ -321,6 +398,7 @@ fn create_padded_schematic(input: String) -> Result<Vec<Vec<Cell>>, Box<dyn Erro
         row.push(Cell::new(0, CellType::Period));
 
         schematic.push(row);
+        lines.push(i + 1);
     }
 
     // Pad top and bottom to minimise out of bound checking.
@@ -335,16 +413,23 @@ fn create_padded_schematic(input: String) -> Result<Vec<Vec<Cell>>, Box<dyn Erro
             .map(|_| Cell::new(0, CellType::Period))
             .collect(),
     );
+    lines.push(0);
 
-    Ok(schematic)
+    Ok(Schematic {
+        rows: schematic,
+        lines,
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{sum_gear_ratios, sum_part_numbers};
+    use super::{create_padded_schematic, sum_gear_ratios, sum_part_numbers};
+    use crate::error::ErrorKind;
 
     fn test_sum_part_numbers_case(input: &str, want: usize) -> Result<(), String> {
-        match sum_part_numbers(String::from(input)) {
+        let schem = create_padded_schematic(input).map_err(|err| err.to_string())?;
+
+        match sum_part_numbers(&schem) {
             Ok(got) => {
                 if got != want {
                     Err(format!("want {}, got {}", want, got))
@@ -352,7 +437,7 @@ mod tests {
                     Ok(())
                 }
             }
-            Err(err) => Err(format!("{}", err.to_string())),
+            Err(err) => Err(format!("{}", err)),
         }
     }
 
@@ -432,13 +517,15 @@ mod tests {
             (r#".........."#, 0),
         ]
         .iter()
-        .try_for_each(|(input, want)| test_sum_part_numbers_case(*input, *want))?;
+        .try_for_each(|(input, want)| test_sum_part_numbers_case(input, *want))?;
 
         Ok(())
     }
 
     fn test_sum_gear_ratios_case(input: &str, want: usize) -> Result<(), String> {
-        match sum_gear_ratios(String::from(input)) {
+        let schem = create_padded_schematic(input).map_err(|err| err.to_string())?;
+
+        match sum_gear_ratios(&schem) {
             Ok(got) => {
                 if got != want {
                     Err(format!("want {}, got {}", want, got))
@@ -446,7 +533,7 @@ mod tests {
                     Ok(())
                 }
             }
-            Err(err) => Err(format!("{}", err.to_string())),
+            Err(err) => Err(format!("{}", err)),
         }
     }
 
@@ -496,8 +583,83 @@ mod tests {
             ),
         ]
         .iter()
-        .try_for_each(|(input, want)| test_sum_gear_ratios_case(*input, *want))?;
+        .try_for_each(|(input, want)| test_sum_gear_ratios_case(input, *want))?;
 
         Ok(())
     }
+
+    #[test]
+    fn test_sum_part_numbers_overflow() {
+        let schem =
+            create_padded_schematic("9999999999999999999*\n9999999999999999999*\n").unwrap();
+
+        let err = sum_part_numbers(&schem).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Overflow(_)), "{}", err);
+        assert_eq!(
+            "line 2, column 1: overflow: the sum of the part numbers doesn't fit in a usize",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_errors_after_blank_lines() {
+        // Blank lines are skipped but still count towards the line numbers.
+        let schem = create_padded_schematic(
+            ".......................\n\n\n.99999999999999999999*.\n\n.......................\n",
+        )
+        .unwrap();
+
+        let want = "line 4, column 2: parse error: invalid part number '99999999999999999999': number too large to fit in target type";
+        assert_eq!(want, sum_part_numbers(&schem).unwrap_err().to_string());
+        assert_eq!(want, sum_gear_ratios(&schem).unwrap_err().to_string());
+
+        let schem = create_padded_schematic("..........\n\n4294967296\n\n*.........\n4294967296\n")
+            .unwrap();
+        assert_eq!(
+            "line 5, column 1: overflow: the gear ratio doesn't fit in a usize",
+            sum_gear_ratios(&schem).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_sum_gear_ratios_errors() {
+        [
+            (
+                "99999999999999999999*1",
+                "line 1, column 1: parse error: invalid part number '99999999999999999999': number too large to fit in target type",
+            ),
+            (
+                "4294967296*4294967296",
+                "line 1, column 11: overflow: the gear ratio doesn't fit in a usize",
+            ),
+            (
+                "4294967295*4294967295\n.....................\n4294967295*4294967295",
+                "overflow: the sum of the gear ratios doesn't fit in a usize",
+            ),
+        ]
+        .iter()
+        .for_each(|(input, want)| {
+            let schem = create_padded_schematic(input).unwrap();
+            assert_eq!(*want, sum_gear_ratios(&schem).unwrap_err().to_string());
+        });
+    }
+
+    #[test]
+    fn test_create_padded_schematic_errors() {
+        [
+            (
+                "467..114..\n...*.....\n",
+                "line 2: malformed record: expected 10 columns, found 9",
+            ),
+            (
+                "467..\n\n...*.\n..35..\n",
+                "line 4: malformed record: expected 5 columns, found 6",
+            ),
+        ]
+        .iter()
+        .for_each(|(input, want)| {
+            let got = create_padded_schematic(input).map(|_| ()).unwrap_err();
+            assert_eq!(*want, got.to_string());
+        });
+    }
 }
@@ -1,86 +1,409 @@
+//! Day 4: Scratchcards. Score each card by how many of its numbers are winning numbers.
+
+use crate::error::{Error, ErrorKind, Result};
+use crate::solution::{ExplainFormat, Options, Solution};
+use num::{BigUint, One, Zero};
+use serde::{Serialize, Serializer};
 use std::collections::HashSet;
-use std::env;
-use std::error::Error;
-use std::fs::File;
-use std::io::Read;
+use std::fmt::Display;
+use std::ops::Range;
 
-struct ScratchCard {
-    winning_numbers: Vec<usize>,
-    card_numbers: Vec<usize>,
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScratchCard {
+    pub id: usize,
+    pub winning_numbers: Vec<usize>,
+    pub card_numbers: Vec<usize>,
 }
 
-pub fn totals() -> Result<(usize, usize), Box<dyn Error>> {
-    let file_path = format!(
-        "{}/data/input_day_04.txt",
-        env::current_dir().unwrap().display()
-    );
-    let mut file = File::open(file_path)?;
-    let mut contents = String::new();
-    let mut scratch_cards: Vec<ScratchCard> = vec![];
+impl ScratchCard {
+    /// The winning numbers found among the card's numbers, each only once and in card order.
+    pub fn matches(&self) -> Vec<usize> {
+        let win: HashSet<_> = self.winning_numbers.iter().collect();
+        let mut seen: HashSet<usize> = HashSet::new();
 
-    file.read_to_string(&mut contents)?;
-    string_to_captures(&contents, &mut scratch_cards)?;
+        self.card_numbers
+            .iter()
+            .filter(|n| win.contains(n) && seen.insert(**n))
+            .cloned()
+            .collect()
+    }
+}
 
-    Ok((sum_scratchcards(&scratch_cards)?, 0))
+/// The parsed cards along with how they should be counted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cards {
+    pub cards: Vec<ScratchCard>,
+    /// Skip straight to arbitrary precision rather than waiting for `usize` to overflow.
+    pub big_integers: bool,
 }
 
-fn string_to_captures(contents: &str, cards: &mut Vec<ScratchCard>) -> Result<(), Box<dyn Error>> {
+/// Part one sums the points of every card, part two counts every card including the copies won.
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+    const EXAMPLE: &'static str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+";
+
+    type Input = Cards;
+    type Answer = BigUint;
+
+    fn parse(input: &str, options: &Options) -> Result<Self::Input> {
+        let mut scratch_cards: Vec<ScratchCard> = vec![];
+        string_to_captures(input, &mut scratch_cards)?;
+
+        Ok(Cards {
+            cards: scratch_cards,
+            big_integers: options.big_integers,
+        })
+    }
+
+    fn part_one(input: &Self::Input) -> Result<Self::Answer> {
+        if input.big_integers {
+            return Ok(big_sum_scratchcards(&input.cards));
+        }
+        or_big(sum_scratchcards(&input.cards), || {
+            big_sum_scratchcards(&input.cards)
+        })
+    }
+
+    fn part_two(input: &Self::Input) -> Result<Self::Answer> {
+        if input.big_integers {
+            return Ok(big_sum_scratchcard_copies(&input.cards));
+        }
+        or_big(sum_scratchcard_copies(&input.cards), || {
+            big_sum_scratchcard_copies(&input.cards)
+        })
+    }
+
+    fn explain(input: &Self::Input, format: ExplainFormat) -> Option<String> {
+        let traces = trace(&input.cards);
+        Some(match format {
+            ExplainFormat::Text => trace_table(&traces),
+            ExplainFormat::Json => trace_json(&traces),
+        })
+    }
+}
+
+/// Parses one card per line, appending them to `cards`. Blank lines are skipped, anything else must
+/// look like `Card <id>: <winning numbers> | <card numbers>` with each id one more than the last
+/// and no number repeated on either side.
+pub fn string_to_captures(contents: &str, cards: &mut Vec<ScratchCard>) -> Result<()> {
+    let mut previous_id: Option<usize> = cards.last().map(|card| card.id);
+
     for line in contents.lines() {
-        let mut winning_numbers: Vec<usize> = vec![];
-        let mut card_numbers: Vec<usize> = vec![];
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_offset = offset_in(contents, line);
+        let malformed = |reason: String, offset: usize| {
+            Error::malformed(reason).at_offset(contents, line_offset + offset)
+        };
 
         // First split on the colon and pipe.
-        let parts: Vec<&str> = line.split(&[':', '|']).collect();
-
-        // Get the digits from each segment and populate the cards.
-        for (part_index, part) in parts.iter().enumerate() {
-            part.trim()
-                .split_whitespace()
-                .for_each(|n| match n.parse::<usize>() {
-                    Ok(parsed_num) => match part_index {
-                        1 => winning_numbers.push(parsed_num),
-                        2 => card_numbers.push(parsed_num),
-                        _ => (),
-                    },
-                    Err(_) => {
-                        return ();
-                    }
-                });
+        let (label, numbers) = line.split_once(':').ok_or_else(|| {
+            malformed(format!("missing \":\" after the card id in '{}'", line), 0)
+        })?;
+        let (winning, card) = numbers.split_once('|').ok_or_else(|| {
+            malformed(
+                String::from("missing \"|\" between the winning and card numbers"),
+                label.len() + 1,
+            )
+        })?;
+        if let Some(extra) = card.find('|') {
+            return Err(malformed(
+                String::from("more than one \"|\" in the card"),
+                offset_in(line, card) + extra,
+            ));
+        }
+
+        let id = match label.split_whitespace().collect::<Vec<&str>>()[..] {
+            ["Card", id] => parse_number(contents, id)?,
+            _ => {
+                return Err(malformed(
+                    format!("expected 'Card <id>', found '{}'", label.trim()),
+                    0,
+                ))
+            }
+        };
+        match previous_id {
+            Some(previous) if id == previous => {
+                return Err(malformed(format!("duplicate card id {}", id), 0))
+            }
+            Some(previous) if id < previous => {
+                return Err(malformed(
+                    format!("card {} is out of order after card {}", id, previous),
+                    0,
+                ))
+            }
+            // Copies are won by the cards that follow, which a gap would hand to the wrong card.
+            Some(previous) if id > previous + 1 => {
+                return Err(malformed(
+                    format!("missing card {} before card {}", previous + 1, id),
+                    0,
+                ))
+            }
+            _ => previous_id = Some(id),
         }
 
         cards.push(ScratchCard {
-            winning_numbers,
-            card_numbers,
+            id,
+            winning_numbers: parse_numbers(contents, winning, "winning numbers")?,
+            card_numbers: parse_numbers(contents, card, "card numbers")?,
         });
     }
 
     Ok(())
 }
 
-fn sum_scratchcards(cards: &Vec<ScratchCard>) -> Result<usize, Box<dyn Error>> {
-    let mut total_points: Vec<usize> = vec![];
+/// The byte offset of `part` within `whole`, which it must be a slice of.
+fn offset_in(whole: &str, part: &str) -> usize {
+    part.as_ptr() as usize - whole.as_ptr() as usize
+}
+
+fn parse_number(contents: &str, token: &str) -> Result<usize> {
+    token.parse::<usize>().map_err(|err| {
+        Error::parse(format!("invalid number '{}': {}", token, err))
+            .at_offset(contents, offset_in(contents, token))
+    })
+}
+
+/// Parses one side of a card, rejecting any number that appears on it twice.
+fn parse_numbers(contents: &str, side: &str, name: &str) -> Result<Vec<usize>> {
+    let mut numbers: Vec<usize> = vec![];
+    for token in side.split_whitespace() {
+        let number = parse_number(contents, token)?;
+        if numbers.contains(&number) {
+            return Err(
+                Error::malformed(format!("duplicate number {} in the {}", number, name))
+                    .at_offset(contents, offset_in(contents, token)),
+            );
+        }
+        numbers.push(number);
+    }
+
+    Ok(numbers)
+}
+
+/// Falls back to counting with arbitrary precision when the `usize` count overflowed.
+fn or_big<F: FnOnce() -> BigUint>(result: Result<usize>, big: F) -> Result<BigUint> {
+    match result {
+        Ok(total) => Ok(BigUint::from(total)),
+        Err(err) if matches!(err.kind(), ErrorKind::Overflow(_)) => Ok(big()),
+        Err(err) => Err(err),
+    }
+}
+
+/// The total points, a card scores one point for its first match and doubles for each match after.
+/// Fails with an overflow error when the points don't fit in a `usize`, see
+/// [`big_sum_scratchcards`].
+pub fn sum_scratchcards(cards: &[ScratchCard]) -> Result<usize> {
+    let mut total_points: usize = 0;
     for card in cards {
-        let num: HashSet<_> = card.card_numbers.clone().into_iter().collect();
-        let win: HashSet<_> = card.winning_numbers.clone().into_iter().collect();
-        let intersection: HashSet<_> = num.intersection(&win).cloned().collect();
         let mut points: usize = 0;
-        for _ in intersection.iter() {
+        for _ in card.matches() {
             if points == 0 {
                 // Award first point.
                 points = 1;
                 continue;
             }
-            points = points * 2;
+            points = points.checked_mul(2).ok_or_else(|| {
+                Error::overflow(format!(
+                    "the points of card {} don't fit in a usize",
+                    card.id
+                ))
+            })?;
         }
-        total_points.push(points);
+        total_points = total_points
+            .checked_add(points)
+            .ok_or_else(|| Error::overflow("the total points don't fit in a usize"))?;
     }
 
-    Ok(total_points.iter().sum())
+    Ok(total_points)
+}
+
+/// Like [`sum_scratchcards`] but never overflows.
+pub fn big_sum_scratchcards(cards: &[ScratchCard]) -> BigUint {
+    cards
+        .iter()
+        .map(|card| match card.matches().len() {
+            0 => BigUint::zero(),
+            matches => BigUint::one() << (matches - 1),
+        })
+        .sum()
+}
+
+/// The positions of the cards won by the card at `i`, which are the cards with the next `won` ids.
+/// Cards are in increasing id order, so these always follow it, and ids that aren't in the table
+/// are never won.
+fn won_cards(cards: &[ScratchCard], i: usize, won: usize) -> Range<usize> {
+    let last_id = cards[i].id.saturating_add(won);
+    let after = &cards[i + 1..];
+    (i + 1)..(i + 1 + after.partition_point(|card| card.id <= last_id))
+}
+
+/// The total number of cards once every copy has been won, a card with `n` matches wins a copy of
+/// each of the cards with the next `n` ids, never going past the end of the table.
+pub fn sum_scratchcard_copies(cards: &[ScratchCard]) -> Result<usize> {
+    // Everyone starts with one of each original card.
+    let mut copies: Vec<usize> = vec![1; cards.len()];
+
+    for (i, card) in cards.iter().enumerate() {
+        let won = card.matches().len();
+        for j in won_cards(cards, i, won) {
+            // Each copy of this card wins its own copy of the following cards.
+            copies[j] = copies[j].checked_add(copies[i]).ok_or_else(|| {
+                Error::overflow(format!(
+                    "the copies of card {} don't fit in a usize",
+                    cards[j].id
+                ))
+            })?;
+        }
+    }
+
+    copies.iter().try_fold(0usize, |total, &n| {
+        total
+            .checked_add(n)
+            .ok_or_else(|| Error::overflow("the total number of cards doesn't fit in a usize"))
+    })
+}
+
+/// Like [`sum_scratchcard_copies`] but never overflows.
+pub fn big_sum_scratchcard_copies(cards: &[ScratchCard]) -> BigUint {
+    let mut copies: Vec<BigUint> = vec![BigUint::one(); cards.len()];
+
+    for (i, card) in cards.iter().enumerate() {
+        let won = card.matches().len();
+        for j in won_cards(cards, i, won) {
+            let add = copies[i].clone();
+            copies[j] += add;
+        }
+    }
+
+    copies.into_iter().sum()
+}
+
+/// How a single card scored and how many copies of it were won.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CardTrace {
+    pub id: usize,
+    /// The winning numbers on the card, in card order.
+    pub matches: Vec<usize>,
+    #[serde(serialize_with = "as_string")]
+    pub points: BigUint,
+    /// The original card plus every copy won from earlier cards.
+    #[serde(serialize_with = "as_string")]
+    pub copies: BigUint,
+    pub won_from: Vec<WonFrom>,
+}
+
+/// Copies of a card won by an earlier card, one for each copy of the earlier card.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct WonFrom {
+    pub card: usize,
+    #[serde(serialize_with = "as_string")]
+    pub copies: BigUint,
+}
+
+/// Big numbers are written as strings, the same as answers in the run report.
+fn as_string<T: Display, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+/// Scores every card and follows the copies it wins, the same as [`big_sum_scratchcards`] and
+/// [`big_sum_scratchcard_copies`] but keeping each step.
+pub fn trace(cards: &[ScratchCard]) -> Vec<CardTrace> {
+    let mut traces: Vec<CardTrace> = cards
+        .iter()
+        .map(|card| {
+            let matches = card.matches();
+            CardTrace {
+                id: card.id,
+                points: match matches.len() {
+                    0 => BigUint::zero(),
+                    n => BigUint::one() << (n - 1),
+                },
+                matches,
+                copies: BigUint::one(),
+                won_from: vec![],
+            }
+        })
+        .collect();
+
+    for i in 0..traces.len() {
+        let won = traces[i].matches.len();
+        for j in won_cards(cards, i, won) {
+            let from = WonFrom {
+                card: traces[i].id,
+                copies: traces[i].copies.clone(),
+            };
+            traces[j].copies += &from.copies;
+            traces[j].won_from.push(from);
+        }
+    }
+
+    traces
+}
+
+/// One row per card followed by the totals for each part.
+pub fn trace_table(traces: &[CardTrace]) -> String {
+    let join = |values: Vec<String>| {
+        if values.is_empty() {
+            String::from("-")
+        } else {
+            values.join(" ")
+        }
+    };
+
+    let mut table = format!(
+        "{:<6}{:<30}{:>8} {:>10}  {}\n",
+        "card", "matches", "points", "copies", "won from"
+    );
+    for trace in traces {
+        table.push_str(&format!(
+            "{:<6}{:<30}{:>8} {:>10}  {}\n",
+            trace.id,
+            join(trace.matches.iter().map(|n| n.to_string()).collect()),
+            trace.points,
+            trace.copies,
+            join(
+                trace
+                    .won_from
+                    .iter()
+                    .map(|from| format!("{}x{}", from.card, from.copies))
+                    .collect()
+            ),
+        ));
+    }
+
+    let points: BigUint = traces.iter().map(|trace| &trace.points).sum();
+    let copies: BigUint = traces.iter().map(|trace| &trace.copies).sum();
+    table.push_str(&format!("{:<36}{:>8} {:>10}\n", "total", points, copies));
+    table
+}
+
+pub fn trace_json(traces: &[CardTrace]) -> String {
+    // Traces only hold strings and numbers so serialising can't fail.
+    serde_json::to_string_pretty(traces).unwrap_or_default() + "\n"
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day_04::{string_to_captures, sum_scratchcards, ScratchCard};
+    use crate::day_04::{
+        big_sum_scratchcard_copies, big_sum_scratchcards, string_to_captures,
+        sum_scratchcard_copies, sum_scratchcards, trace, trace_json, trace_table, Day04,
+        ScratchCard,
+    };
+    use crate::error::ErrorKind;
+    use crate::solution::{Options, Solution};
 
     fn test_sum_gear_ratios_case(input: &str, want: usize) -> Result<(), String> {
         let contents = String::from(input);
@@ -96,7 +419,7 @@ mod tests {
                     Ok(())
                 }
             }
-            Err(err) => Err(format!("{}", err.to_string())),
+            Err(err) => Err(format!("{}", err)),
         }
     }
 
@@ -110,8 +433,300 @@ mod tests {
             (r#"Card   1: 69 72 87 33 61 15  8 78 43 50 | 96 33 86 53 15 82 50 85 61  8 98 72 43 63 45 78 87 69 10 34 73 88 65 27 19"#, 512),
         ]
         .iter()
-        .try_for_each(|(input, want)| test_sum_gear_ratios_case(*input, *want))?;
+        .try_for_each(|(input, want)| test_sum_gear_ratios_case(input, *want))?;
 
         Ok(())
     }
+
+    fn test_sum_scratchcard_copies_case(input: &str, want: usize) -> Result<(), String> {
+        let mut scratch_cards: Vec<ScratchCard> = vec![];
+        string_to_captures(input, &mut scratch_cards).map_err(|err| err.to_string())?;
+
+        match sum_scratchcard_copies(&scratch_cards) {
+            Ok(got) => {
+                if got != want {
+                    Err(format!("want {}, got {}", want, got))
+                } else {
+                    Ok(())
+                }
+            }
+            Err(err) => Err(format!("{}", err)),
+        }
+    }
+
+    #[test]
+    fn test_sum_scratchcard_copies() -> Result<(), String> {
+        [
+            (r#""#, 0),
+            (r#"Card 1: 1 2 | 3 4"#, 1),
+            (r#"Card 1: 1 2 | 1 2"#, 1),
+            (
+                r#"Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"#,
+                30,
+            ),
+            // Copies that would run past the end of the table are never won.
+            (
+                r#"Card 1: 1 2 3 | 1 2 3
+Card 2: 1 2 3 | 1 2 3"#,
+                3,
+            ),
+            (
+                r#"Card 1: 1 | 2
+Card 2: 1 2 3 4 5 | 1 2 3 4 5
+Card 3: 1 | 2"#,
+                4,
+            ),
+        ]
+        .iter()
+        .try_for_each(|(input, want)| test_sum_scratchcard_copies_case(input, *want))?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sum_scratchcard_copies_by_id() {
+        // Cards put together without parsing can skip an id, copies of it are never won.
+        let card = |id: usize, numbers: Vec<usize>| ScratchCard {
+            id,
+            winning_numbers: numbers.clone(),
+            card_numbers: numbers,
+        };
+        let cards = vec![card(1, vec![1]), card(3, vec![2, 3]), card(4, vec![])];
+
+        assert_eq!(4, sum_scratchcard_copies(&cards).unwrap());
+        assert_eq!("4", big_sum_scratchcard_copies(&cards).to_string());
+    }
+
+    #[test]
+    fn test_sum_scratchcard_copies_large_piles() -> Result<(), String> {
+        // When every card wins a copy of every card after it the piles double, card n ends up
+        // with 2^(n-1) copies and the total is 2^n - 1.
+        let cards = 40;
+        let input: Vec<String> = (1..=cards)
+            .map(|id| {
+                let numbers: Vec<String> = (1..=cards).map(|n| n.to_string()).collect();
+                format!("Card {}: {} | {}", id, numbers.join(" "), numbers.join(" "))
+            })
+            .collect();
+
+        test_sum_scratchcard_copies_case(&input.join("\n"), (1 << cards) - 1)
+    }
+
+    #[test]
+    fn test_string_to_captures() {
+        let mut cards: Vec<ScratchCard> = vec![];
+        string_to_captures("Card 1: 41 48 | 83 41\n\nCard   2: | 7\n", &mut cards).unwrap();
+
+        assert_eq!(
+            vec![
+                ScratchCard {
+                    id: 1,
+                    winning_numbers: vec![41, 48],
+                    card_numbers: vec![83, 41],
+                },
+                ScratchCard {
+                    id: 2,
+                    winning_numbers: vec![],
+                    card_numbers: vec![7],
+                },
+            ],
+            cards
+        );
+    }
+
+    fn test_string_to_captures_errors_case(input: &str, want: &str) -> Result<(), String> {
+        let mut cards: Vec<ScratchCard> = vec![];
+
+        match string_to_captures(input, &mut cards) {
+            Ok(()) => Err(format!("want error {}, got {:?}", want, cards)),
+            Err(err) => {
+                let got = err.to_string();
+                if got != want {
+                    Err(format!("want {}, got {}", want, got))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_string_to_captures_errors() -> Result<(), String> {
+        [
+            (
+                "Card 1: 1 2 | 3\nCard 2: 1 2 3",
+                "line 2, column 8: malformed record: missing \"|\" between the winning and card numbers",
+            ),
+            (
+                "Card 1 1 2 | 3",
+                "line 1, column 1: malformed record: missing \":\" after the card id in 'Card 1 1 2 | 3'",
+            ),
+            (
+                "Card 1: 1 | 2 | 3",
+                "line 1, column 15: malformed record: more than one \"|\" in the card",
+            ),
+            (
+                "Game 1: 1 | 2",
+                "line 1, column 1: malformed record: expected 'Card <id>', found 'Game 1'",
+            ),
+            (
+                "Card 1: 1 | 2\nCard 1: 3 | 4",
+                "line 2, column 1: malformed record: duplicate card id 1",
+            ),
+            (
+                "Card 2: 1 | 2\nCard 1: 3 | 4",
+                "line 2, column 1: malformed record: card 1 is out of order after card 2",
+            ),
+            (
+                "Card 1: 1 | 1\nCard 3: 2 | 3\nCard 4: 4 | 5",
+                "line 2, column 1: malformed record: missing card 2 before card 3",
+            ),
+            (
+                "Card 1: 1 2 1 | 3",
+                "line 1, column 13: malformed record: duplicate number 1 in the winning numbers",
+            ),
+            (
+                "Card 1: 1 | 3 4 3",
+                "line 1, column 17: malformed record: duplicate number 3 in the card numbers",
+            ),
+            (
+                "Card 1: 1 | 3 x4",
+                "line 1, column 15: parse error: invalid number 'x4': invalid digit found in string",
+            ),
+            (
+                "Card one: 1 | 3",
+                "line 1, column 6: parse error: invalid number 'one': invalid digit found in string",
+            ),
+        ]
+        .iter()
+        .try_for_each(|(input, want)| test_string_to_captures_errors_case(input, want))?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_to_captures_error_kinds() {
+        let mut cards: Vec<ScratchCard> = vec![];
+        let err = string_to_captures("Card 1: 1 | 3\nCard 2: 4 5", &mut cards).unwrap_err();
+
+        assert!(matches!(err.kind(), ErrorKind::Malformed(_)));
+        assert_eq!(Some(2), err.line());
+    }
+
+    /// `cards` cards that each have every number from 1 to `numbers` on both sides.
+    fn every_number_matches(cards: usize, numbers: usize) -> String {
+        let numbers: Vec<String> = (1..=numbers).map(|n| n.to_string()).collect();
+        (1..=cards)
+            .map(|id| {
+                format!(
+                    "Card {}: {} | {}\n",
+                    id,
+                    numbers.join(" "),
+                    numbers.join(" ")
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_big_integers() {
+        [
+            // 2^69 points from the one card, and 2^70 - 1 cards.
+            (1, 70, "590295810358705651712", "1"),
+            (70, 70, "41320706725109395619840", "1180591620717411303423"),
+            // Small enough for a usize, the same answers either way.
+            (6, 6, "192", "63"),
+        ]
+        .iter()
+        .for_each(|(cards, numbers, part_one, part_two)| {
+            let input = every_number_matches(*cards, *numbers);
+            let mut scratch_cards: Vec<ScratchCard> = vec![];
+            string_to_captures(&input, &mut scratch_cards).unwrap();
+
+            assert_eq!(*part_one, big_sum_scratchcards(&scratch_cards).to_string());
+            assert_eq!(
+                *part_two,
+                big_sum_scratchcard_copies(&scratch_cards).to_string()
+            );
+
+            // The usize path either agrees or overflows, and the solution switches over on its own.
+            for big_integers in [false, true] {
+                let options = Options {
+                    big_integers,
+                    ..Options::default()
+                };
+                let parsed = Day04::parse(&input, &options).unwrap();
+                assert_eq!(*part_one, Day04::part_one(&parsed).unwrap().to_string());
+                assert_eq!(*part_two, Day04::part_two(&parsed).unwrap().to_string());
+            }
+        });
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut scratch_cards: Vec<ScratchCard> = vec![];
+        string_to_captures(&every_number_matches(70, 70), &mut scratch_cards).unwrap();
+
+        [
+            sum_scratchcards(&scratch_cards).unwrap_err(),
+            sum_scratchcard_copies(&scratch_cards).unwrap_err(),
+        ]
+        .iter()
+        .for_each(|err| assert!(matches!(err.kind(), ErrorKind::Overflow(_)), "{}", err));
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut scratch_cards: Vec<ScratchCard> = vec![];
+        string_to_captures(Day04::EXAMPLE, &mut scratch_cards).unwrap();
+        let traces = trace(&scratch_cards);
+
+        assert_eq!(
+            r#"card  matches                         points     copies  won from
+1     83 86 17 48                          8          1  -
+2     61 32                                2          2  1x1
+3     21 1                                 2          4  1x1 2x2
+4     84                                   1          8  1x1 2x2 3x4
+5     -                                    0         14  1x1 3x4 4x8
+6     -                                    0          1  -
+total                                     13         30
+"#,
+            trace_table(&traces)
+        );
+
+        // A card missing from the table takes its copies with it, the same as in the answer.
+        let card = |id: usize, numbers: Vec<usize>| ScratchCard {
+            id,
+            winning_numbers: numbers.clone(),
+            card_numbers: numbers,
+        };
+        let gap = trace(&[card(1, vec![1]), card(3, vec![2, 3]), card(4, vec![])]);
+        assert_eq!(
+            vec![vec![], vec![], vec![3]],
+            gap.iter()
+                .map(|trace| trace.won_from.iter().map(|from| from.card).collect())
+                .collect::<Vec<Vec<usize>>>()
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&trace_json(&traces)).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "id": 4,
+                "matches": [84],
+                "points": "1",
+                "copies": "8",
+                "won_from": [
+                    {"card": 1, "copies": "1"},
+                    {"card": 2, "copies": "2"},
+                    {"card": 3, "copies": "4"},
+                ],
+            }),
+            json[3]
+        );
+    }
 }
@@ -0,0 +1,222 @@
+//! The error returned by every day, see [`Error`].
+
+use crate::solution::Part;
+use std::fmt;
+use std::io;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Why a day failed, see [`Error`] for where it failed.
+#[derive(Debug)]
+pub enum ErrorKind {
+    /// The input could not be read.
+    Io(io::Error),
+    /// A value in the input could not be parsed, such as a number that is out of range.
+    Parse(String),
+    /// A record in the input does not have the expected structure.
+    Malformed(String),
+    /// The input has nothing in it to solve.
+    EmptyInput,
+    /// An answer is too large for the integer type it is counted in.
+    Overflow(String),
+    /// The day doesn't support what was asked of it.
+    Unsupported(String),
+}
+
+/// A failure while solving a puzzle, carrying as much of its location as is known.
+///
+/// Lines and columns are one based so they can be matched against an editor.
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    day: Option<u8>,
+    part: Option<Part>,
+    line: Option<usize>,
+    column: Option<usize>,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind) -> Self {
+        Error {
+            kind,
+            day: None,
+            part: None,
+            line: None,
+            column: None,
+        }
+    }
+
+    pub fn parse<S: Into<String>>(reason: S) -> Self {
+        Error::new(ErrorKind::Parse(reason.into()))
+    }
+
+    pub fn malformed<S: Into<String>>(reason: S) -> Self {
+        Error::new(ErrorKind::Malformed(reason.into()))
+    }
+
+    pub fn empty_input() -> Self {
+        Error::new(ErrorKind::EmptyInput)
+    }
+
+    pub fn overflow<S: Into<String>>(reason: S) -> Self {
+        Error::new(ErrorKind::Overflow(reason.into()))
+    }
+
+    pub fn unsupported<S: Into<String>>(reason: S) -> Self {
+        Error::new(ErrorKind::Unsupported(reason.into()))
+    }
+
+    /// Sets the day, keeping any day that has already been set by a more specific caller.
+    pub fn in_day(mut self, day: u8) -> Self {
+        self.day.get_or_insert(day);
+        self
+    }
+
+    pub fn in_part(mut self, part: Part) -> Self {
+        self.part.get_or_insert(part);
+        self
+    }
+
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line.get_or_insert(line);
+        self
+    }
+
+    pub fn at_column(mut self, column: usize) -> Self {
+        self.column.get_or_insert(column);
+        self
+    }
+
+    /// Sets the line and column from a byte offset into the whole input.
+    pub fn at_offset(self, input: &str, offset: usize) -> Self {
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        self.at_line(before.matches('\n').count() + 1)
+            .at_column(before[line_start..].chars().count() + 1)
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    pub fn day(&self) -> Option<u8> {
+        self.day
+    }
+
+    pub fn part(&self) -> Option<Part> {
+        self.part
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    pub fn column(&self) -> Option<usize> {
+        self.column
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::Io(err) => write!(f, "i/o error: {}", err),
+            ErrorKind::Parse(reason) => write!(f, "parse error: {}", reason),
+            ErrorKind::Malformed(reason) => write!(f, "malformed record: {}", reason),
+            ErrorKind::EmptyInput => write!(f, "empty input"),
+            ErrorKind::Overflow(reason) => write!(f, "overflow: {}", reason),
+            ErrorKind::Unsupported(reason) => write!(f, "unsupported: {}", reason),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut location: Vec<String> = vec![];
+        if let Some(day) = self.day {
+            location.push(format!("day {:02}", day));
+        }
+        if let Some(part) = self.part {
+            location.push(part.to_string());
+        }
+        if let Some(line) = self.line {
+            location.push(format!("line {}", line));
+        }
+        if let Some(column) = self.column {
+            location.push(format!("column {}", column));
+        }
+
+        if location.is_empty() {
+            write!(f, "{}", self.kind)
+        } else {
+            write!(f, "{}: {}", location.join(", "), self.kind)
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::new(ErrorKind::Io(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+    use crate::solution::Part;
+
+    #[test]
+    fn test_display() {
+        [
+            (Error::empty_input(), "empty input"),
+            (
+                Error::parse("invalid digit").in_day(3).at_line(2),
+                "day 03, line 2: parse error: invalid digit",
+            ),
+            (
+                Error::malformed("missing '|'")
+                    .in_day(4)
+                    .in_part(Part::Two)
+                    .at_line(7)
+                    .at_column(12),
+                "day 04, part two, line 7, column 12: malformed record: missing '|'",
+            ),
+            (
+                Error::parse("invalid digit")
+                    .at_line(2)
+                    .at_line(5)
+                    .in_day(1),
+                "day 01, line 2: parse error: invalid digit",
+            ),
+        ]
+        .iter()
+        .for_each(|(err, want)| assert_eq!(*want, err.to_string()));
+    }
+
+    #[test]
+    fn test_at_offset() {
+        let input = "Game 1: 3 blue\nGame 2: 4 red\n";
+
+        [
+            (0, "line 1, column 1"),
+            (5, "line 1, column 6"),
+            (15, "line 2, column 1"),
+            (23, "line 2, column 9"),
+            (29, "line 3, column 1"),
+        ]
+        .iter()
+        .for_each(|(offset, want)| {
+            let err = Error::parse("bad").at_offset(input, *offset);
+            assert_eq!(format!("{}: parse error: bad", want), err.to_string());
+        });
+    }
+}
@@ -0,0 +1,156 @@
+//! Where puzzle input is read from, see [`InputSource`].
+
+use crate::error::Result;
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// Name of the environment variable pointing at a directory of `input_day_NN.txt` files.
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+/// Where a day's puzzle input is read from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputSource {
+    /// A single file, used as is for whichever day is run.
+    File(PathBuf),
+    /// Everything on standard input.
+    Stdin,
+    /// A directory holding one `input_day_NN.txt` file per day.
+    Directory(PathBuf),
+    /// Input compiled into the binary, such as a puzzle's example.
+    Embedded(&'static str),
+}
+
+impl InputSource {
+    /// The directory named by `AOC_INPUT_DIR`, falling back to the `data` directory of the crate.
+    pub fn from_env() -> Self {
+        InputSource::Directory(resolve_directory(env::var_os(INPUT_DIR_VAR)))
+    }
+
+    pub fn read(&self, day: u8) -> Result<String> {
+        match self {
+            InputSource::File(path) => read_file(path),
+            InputSource::Stdin => {
+                let mut contents = String::new();
+                io::stdin().read_to_string(&mut contents)?;
+                Ok(contents)
+            }
+            InputSource::Directory(dir) => read_file(&dir.join(file_name(day))),
+            InputSource::Embedded(contents) => Ok(String::from(*contents)),
+        }
+    }
+
+    /// Opens the input to be read a bit at a time, for days that don't need all of it at once.
+    pub fn open(&self, day: u8) -> Result<Box<dyn BufRead>> {
+        match self {
+            InputSource::File(path) => open_file(path),
+            InputSource::Stdin => Ok(Box::new(io::stdin().lock())),
+            InputSource::Directory(dir) => open_file(&dir.join(file_name(day))),
+            InputSource::Embedded(contents) => Ok(Box::new(contents.as_bytes())),
+        }
+    }
+}
+
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputSource::File(path) => write!(f, "{}", path.display()),
+            InputSource::Stdin => write!(f, "stdin"),
+            InputSource::Directory(dir) => write!(f, "{}", dir.display()),
+            InputSource::Embedded(_) => write!(f, "embedded input"),
+        }
+    }
+}
+
+/// The name of a day's input file within an input directory, such as `input_day_01.txt`.
+pub fn file_name(day: u8) -> String {
+    format!("input_day_{:02}.txt", day)
+}
+
+/// Relative directories are taken from the crate root rather than the current directory so the
+/// binary behaves the same wherever it is run from.
+fn resolve_directory(value: Option<OsString>) -> PathBuf {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    match value {
+        Some(dir) if !dir.is_empty() => root.join(dir),
+        _ => root.join("data"),
+    }
+}
+
+fn read_file(path: &Path) -> Result<String> {
+    // Keep the path in the message, io::Error on its own doesn't say which file was missing.
+    fs::read_to_string(path).map_err(|err| {
+        io::Error::new(
+            err.kind(),
+            format!("unable to read {}: {}", path.display(), err),
+        )
+        .into()
+    })
+}
+
+fn open_file(path: &Path) -> Result<Box<dyn BufRead>> {
+    let file = fs::File::open(path).map_err(|err| {
+        io::Error::new(
+            err.kind(),
+            format!("unable to read {}: {}", path.display(), err),
+        )
+    })?;
+    Ok(Box::new(BufReader::new(file)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{file_name, resolve_directory, InputSource};
+    use std::ffi::OsString;
+    use std::io::Read;
+    use std::path::Path;
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!("input_day_01.txt", file_name(1));
+        assert_eq!("input_day_25.txt", file_name(25));
+    }
+
+    #[test]
+    fn test_resolve_directory() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+        assert_eq!(root.join("data"), resolve_directory(None));
+        assert_eq!(root.join("data"), resolve_directory(Some(OsString::new())));
+        assert_eq!(
+            root.join("inputs"),
+            resolve_directory(Some(OsString::from("inputs")))
+        );
+        assert_eq!(
+            Path::new("/tmp/inputs"),
+            resolve_directory(Some(OsString::from("/tmp/inputs")))
+        );
+    }
+
+    #[test]
+    fn test_read() {
+        let embedded = InputSource::Embedded("1abc2\n");
+        assert_eq!("1abc2\n", embedded.read(1).unwrap());
+
+        let data = InputSource::Directory(resolve_directory(None));
+        assert!(!data.read(1).unwrap().is_empty());
+
+        let missing = InputSource::Directory(resolve_directory(None));
+        assert!(missing.read(99).is_err());
+    }
+
+    #[test]
+    fn test_open() {
+        let data = InputSource::Directory(resolve_directory(None));
+        let mut contents = String::new();
+        data.open(1).unwrap().read_to_string(&mut contents).unwrap();
+        assert_eq!(data.read(1).unwrap(), contents);
+
+        let missing = InputSource::Directory(resolve_directory(None));
+        let err = missing.open(99).map(|_| ()).unwrap_err().to_string();
+        assert!(err.contains("input_day_99.txt"), "{}", err);
+    }
+}
@@ -0,0 +1,17 @@
+//! Solutions to the [Advent of Code](https://adventofcode.com/) 2023 puzzles.
+//!
+//! Each day lives in its own module and implements [`solution::Solution`], exposing its parser and
+//! the solver for each part so they can be used on their own. [`solution::registry`] holds every
+//! day behind [`solution::Runner`] for callers that want to run them generically.
+
+pub mod answers;
+pub mod bench;
+pub mod day_01;
+pub mod day_02;
+pub mod day_03;
+pub mod day_04;
+pub mod error;
+pub mod input;
+pub mod report;
+pub mod rng;
+pub mod solution;
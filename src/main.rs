@@ -1,41 +1,461 @@
-mod day_01;
-mod day_02;
-mod day_03;
-mod day_04;
+mod cli;
 
-fn main() {
-    // Day one.
-    if let Err(err) = day_01::part_one() {
-        eprintln!("Error: {}", err);
+use aoc23::answers::{self, Answers};
+use aoc23::bench;
+use aoc23::day_02::{self, format, generate, infer, query, Day02};
+use aoc23::error;
+use aoc23::input::InputSource;
+use aoc23::report::{self, Format, Record, Status};
+use aoc23::solution::{self, ExplainFormat, Part, Runner, Solution};
+use cli::{
+    Bench, Command, Explain, Generate, Infer, Input, Query, Reformat, Run, Selection, Verify,
+};
+use std::fs;
+use std::io::{self, BufWriter};
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let command = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, cli::USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match command {
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            ExitCode::SUCCESS
+        }
+        Command::Run(options) => run(&options),
+        Command::Bench(options) => run_bench(&options),
+        Command::Verify(options) => run_verify(&options),
+        Command::Explain(options) => run_explain(&options),
+        Command::Infer(options) => run_infer(&options),
+        Command::Query(options) => run_query(&options),
+        Command::Reformat(options) => run_format(&options),
+        Command::Generate(options) => run_generate(&options),
+    }
+}
+
+/// The registered days matching the selection, or an error naming any days that don't exist.
+fn select(selection: &Selection) -> Result<Vec<Box<dyn Runner>>, String> {
+    let registry = solution::registry_with(&selection.options);
+    let available: Vec<u8> = registry.iter().map(|runner| runner.day()).collect();
+    let days = selection.days.clone().unwrap_or_else(|| available.clone());
+
+    // Check the whole selection up front so nothing runs if any of it is unknown.
+    let unknown: Vec<String> = days
+        .iter()
+        .filter(|day| !available.contains(day))
+        .map(|day| day.to_string())
+        .collect();
+    if !unknown.is_empty() {
+        return Err(format!(
+            "unknown day(s) {}, available days are {}",
+            unknown.join(", "),
+            available
+                .iter()
+                .map(|day| day.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        ));
+    }
+
+    Ok(registry
+        .into_iter()
+        .filter(|runner| days.contains(&runner.day()))
+        .collect())
+}
+
+fn input_source(runner: &dyn Runner, input: &Input) -> InputSource {
+    match input {
+        Input::Default => InputSource::from_env(),
+        Input::Example => InputSource::Embedded(runner.example()),
+        Input::Source(source) => source.clone(),
+    }
+}
+
+fn read_input(runner: &dyn Runner, input: &Input) -> error::Result<String> {
+    input_source(runner, input)
+        .read(runner.day())
+        .map_err(|err| err.in_day(runner.day()))
+}
+
+fn run(options: &Run) -> ExitCode {
+    let runners = match select(&options.selection) {
+        Ok(runners) => runners,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut records: Vec<Record> = vec![];
+    for runner in runners {
+        let day_records = run_day(
+            runner.as_ref(),
+            &options.selection.input,
+            &options.selection.parts,
+            options.stream,
+        );
+
+        // Text is printed as each day finishes, the structured formats need every record first.
+        if options.output == Format::Text {
+            print!("{}", report::render(&day_records, Format::Text));
+            // A day that failed to parse has the same error on every part, so only show it once.
+            let mut errors: Vec<&String> = day_records
+                .iter()
+                .filter_map(|record| record.error.as_ref())
+                .collect();
+            errors.dedup();
+            for error in errors {
+                eprintln!("error: {}", error);
+            }
+        }
+        records.extend(day_records);
+    }
+
+    if options.output != Format::Text {
+        print!("{}", report::render(&records, options.output));
     }
-    if let Err(err) = day_01::part_two() {
-        eprintln!("Error: {}", err);
+
+    if records.iter().any(|record| record.status == Status::Error) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Runs the parts of a single day, a day that can't be read or parsed has an error for each part.
+fn run_day(runner: &dyn Runner, input: &Input, parts: &[Part], stream: bool) -> Vec<Record> {
+    let runs = if stream {
+        input_source(runner, input)
+            .open(runner.day())
+            .map_err(|err| err.in_day(runner.day()))
+            .and_then(|mut reader| runner.stream(&mut reader, parts))
+    } else {
+        read_input(runner, input).and_then(|input| runner.run(&input, parts))
+    };
+
+    match runs {
+        Ok(runs) => runs
+            .iter()
+            .map(|run| Record::from_run(runner.day(), run))
+            .collect(),
+        Err(err) => parts
+            .iter()
+            .map(|&part| Record::from_error(runner.day(), part, &err))
+            .collect(),
     }
+}
 
-    // Day two.
-    match day_02::totals() {
-        Ok(totals) => {
-            println!("day 02: total - part one: {}", totals.0);
-            println!("day 02: total - part two: {}", totals.1);
+fn run_bench(options: &Bench) -> ExitCode {
+    let runners = match select(&options.selection) {
+        Ok(runners) => runners,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut failed = false;
+    let mut stats: Vec<bench::Stats> = vec![];
+    for runner in runners {
+        let result = read_input(runner.as_ref(), &options.selection.input).and_then(|input| {
+            bench::bench(
+                runner.as_ref(),
+                &input,
+                &options.selection.parts,
+                options.iterations,
+            )
+        });
+
+        match result {
+            Ok(day_stats) => stats.extend(day_stats),
+            Err(err) => {
+                eprintln!("error: {}", err);
+                failed = true;
+            }
         }
-        Err(err) => eprintln!("error: {}", err),
     }
 
-    // Day three.
-    match day_03::totals() {
-        Ok(totals) => {
-            println!("day 03: total - part one: {}", totals.0);
-            println!("day 03: total - part two: {}", totals.1);
+    print!("{}", bench::to_table(&stats));
+
+    if let Some(path) = &options.summary {
+        let csv = bench::to_csv(&stats);
+        if path.as_os_str() == "-" {
+            print!("\n{}", csv);
+        } else if let Err(err) = fs::write(path, csv) {
+            eprintln!("error: unable to write {}: {}", path.display(), err);
+            failed = true;
         }
-        Err(err) => eprintln!("error: {}", err),
     }
 
-    // Day four.
-    match day_04::totals() {
-        Ok(totals) => {
-            println!("day 04: total - part one: {}", totals.0);
-            println!("day 04: total - part two: {}", totals.1);
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn run_verify(options: &Verify) -> ExitCode {
+    let runners = match select(&options.selection) {
+        Ok(runners) => runners,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::from(2);
+        }
+    };
+
+    let path = options
+        .answers
+        .clone()
+        .unwrap_or_else(Answers::default_path);
+    let mut recorded = match Answers::load(&path) {
+        Ok(recorded) => recorded,
+        Err(err) => {
+            eprintln!("error: {}: {}", path.display(), err);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut checks: Vec<answers::Check> = vec![];
+    for runner in runners {
+        let results = read_input(runner.as_ref(), &options.selection.input)
+            .and_then(|input| runner.run(&input, &options.selection.parts));
+
+        match results {
+            Ok(results) => {
+                for run in results {
+                    let check = answers::check(
+                        &mut recorded,
+                        runner.day(),
+                        run.part,
+                        &run.answer,
+                        options.record,
+                    );
+                    checks.push(check);
+                }
+            }
+            // Without an input there is nothing to compare, so every selected part is an error.
+            Err(err) => {
+                eprintln!("error: {}", err);
+                for &part in &options.selection.parts {
+                    checks.push(answers::Check {
+                        day: runner.day(),
+                        part,
+                        expected: recorded.get(runner.day(), part).map(String::from),
+                        actual: String::from("-"),
+                        status: answers::Status::Error,
+                    });
+                }
+            }
         }
-        Err(err) => eprintln!("error: {}", err),
     }
+
+    print!("{}", answers::to_table(&checks));
+
+    let mut failed = checks.iter().any(|check| check.status.is_failure());
+    if checks
+        .iter()
+        .any(|check| check.status == answers::Status::Recorded)
+    {
+        if let Err(err) = recorded.save(&path) {
+            eprintln!("error: {}", err);
+            failed = true;
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn run_explain(options: &Explain) -> ExitCode {
+    let runners = match select(&options.selection) {
+        Ok(runners) => runners,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut failed = false;
+    for runner in runners {
+        let explanation = read_input(runner.as_ref(), &options.selection.input)
+            .and_then(|input| runner.explain(&input, options.output));
+
+        match explanation {
+            Ok(Some(explanation)) => print!("{}", explanation),
+            Ok(None) => {
+                eprintln!("error: day {:02} can't be explained", runner.day());
+                failed = true;
+            }
+            Err(err) => {
+                eprintln!("error: {}", err);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn run_infer(options: &Infer) -> ExitCode {
+    let runners = match select(&options.selection) {
+        Ok(runners) => runners,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::from(2);
+        }
+    };
+
+    // The command line only allows day 2 so the selection holds just the one runner.
+    let parsed = read_input(runners[0].as_ref(), &options.selection.input).and_then(|input| {
+        Day02::parse(&input, &options.selection.options).map_err(|err| err.in_day(Day02::DAY))
+    });
+    let parsed = match parsed {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let games: Vec<day_02::Game> = match &options.games {
+        Some(ids) => parsed
+            .games
+            .into_iter()
+            .filter(|game| ids.iter().any(|range| range.contains(&game.id)))
+            .collect(),
+        None => parsed.games,
+    };
+    if games.is_empty() {
+        eprintln!("error: none of the selected games are in the input");
+        return ExitCode::FAILURE;
+    }
+
+    let estimate = infer::infer_bag(&games, &parsed.bag);
+    match options.output {
+        ExplainFormat::Text => print!("{}", infer::to_table(&estimate)),
+        ExplainFormat::Json => print!("{}", infer::to_json(&estimate)),
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn run_query(options: &Query) -> ExitCode {
+    let runners = match select(&options.selection) {
+        Ok(runners) => runners,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::from(2);
+        }
+    };
+
+    // The command line only allows day 2 so the selection holds just the one runner.
+    let parsed = read_input(runners[0].as_ref(), &options.selection.input).and_then(|input| {
+        Day02::parse(&input, &options.selection.options).map_err(|err| err.in_day(Day02::DAY))
+    });
+    let parsed = match parsed {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let expr = match query::parse_query(&options.expression, &parsed.bag) {
+        Ok(expr) => expr,
+        Err(err) => {
+            eprintln!("error: query {}", err);
+            return ExitCode::from(2);
+        }
+    };
+
+    let result = match query::query(&parsed.games, &parsed.bag, &expr) {
+        Ok(result) => result,
+        Err(err) => {
+            eprintln!("error: {}", err.in_day(Day02::DAY));
+            return ExitCode::FAILURE;
+        }
+    };
+    match options.output {
+        ExplainFormat::Text => print!("{}", query::to_text(&result)),
+        ExplainFormat::Json => print!("{}", query::to_json(&result)),
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn run_format(options: &Reformat) -> ExitCode {
+    let runners = match select(&options.selection) {
+        Ok(runners) => runners,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::from(2);
+        }
+    };
+
+    // The command line only allows day 2 so the selection holds just the one runner.
+    let games = read_input(runners[0].as_ref(), &options.selection.input).and_then(|input| {
+        match options.from {
+            ExplainFormat::Text => {
+                Day02::parse(&input, &options.selection.options).map(|parsed| parsed.games)
+            }
+            ExplainFormat::Json => {
+                let bag = options.selection.options.bag.clone();
+                format::from_json(&input, &bag.unwrap_or_else(day_02::default_bag))
+            }
+        }
+        .map_err(|err| err.in_day(Day02::DAY))
+    });
+    let games = match games {
+        Ok(games) => games,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match options.output {
+        ExplainFormat::Text => print!("{}", format::format_games(&games)),
+        ExplainFormat::Json => print!("{}", format::to_json(&games)),
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn run_generate(options: &Generate) -> ExitCode {
+    // The input goes to stdout on its own so it can be redirected straight to a file.
+    let stdout = io::stdout().lock();
+    let generated = match generate::generate(&options.config, BufWriter::new(stdout)) {
+        Ok(generated) => generated,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return match err.kind() {
+                error::ErrorKind::Unsupported(_) => ExitCode::from(2),
+                _ => ExitCode::FAILURE,
+            };
+        }
+    };
+
+    let bag: Vec<String> = generated
+        .bag
+        .iter()
+        .map(|(colour, count)| format!("{} {}", count, colour))
+        .collect();
+    eprintln!("bag: {}", bag.join(", "));
+    eprintln!("part 1: {}", generated.possible);
+    eprintln!("part 2: {}", generated.power);
+
+    ExitCode::SUCCESS
 }
@@ -0,0 +1,314 @@
+//! Structured output of a run for dashboards and CI, see [`Format`] and [`render`].
+
+use crate::error::Error;
+use crate::solution::{Part, PartRun};
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Format {
+    /// One line per answer, errors go to stderr.
+    #[default]
+    Text,
+    Json,
+    Csv,
+    Junit,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "junit" => Ok(Format::Junit),
+            _ => Err(format!(
+                "unknown output format '{}', expected text, json, csv or junit",
+                value
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Ok,
+    Error,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Ok => write!(f, "ok"),
+            Status::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// The outcome of a single part, with exactly one of `answer` and `error` set.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Record {
+    pub day: u8,
+    pub part: u8,
+    pub answer: Option<String>,
+    pub duration_ns: u128,
+    pub status: Status,
+    pub error: Option<String>,
+}
+
+impl Record {
+    pub fn from_run(day: u8, run: &PartRun) -> Self {
+        let (answer, status, error) = match &run.answer {
+            Ok(answer) => (Some(answer.clone()), Status::Ok, None),
+            Err(err) => (None, Status::Error, Some(err.to_string())),
+        };
+
+        Record {
+            day,
+            part: run.part.number(),
+            answer,
+            duration_ns: run.duration.as_nanos(),
+            status,
+            error,
+        }
+    }
+
+    /// A part that never ran because its input could not be read or parsed.
+    pub fn from_error(day: u8, part: Part, err: &Error) -> Self {
+        Record {
+            day,
+            part: part.number(),
+            answer: None,
+            duration_ns: Duration::ZERO.as_nanos(),
+            status: Status::Error,
+            error: Some(err.to_string()),
+        }
+    }
+}
+
+pub fn render(records: &[Record], format: Format) -> String {
+    match format {
+        Format::Text => to_text(records),
+        Format::Json => to_json(records),
+        Format::Csv => to_csv(records),
+        Format::Junit => to_junit(records),
+    }
+}
+
+fn to_text(records: &[Record]) -> String {
+    let mut text = String::new();
+    for record in records {
+        if let Some(answer) = &record.answer {
+            let part = Part::from_number(record.part).unwrap_or(Part::One);
+            text.push_str(&format!(
+                "day {:02}: total - {}: {}\n",
+                record.day, part, answer
+            ));
+        }
+    }
+    text
+}
+
+fn to_json(records: &[Record]) -> String {
+    // Records only hold strings and numbers so serialising can't fail.
+    serde_json::to_string_pretty(records).unwrap_or_default() + "\n"
+}
+
+fn to_csv(records: &[Record]) -> String {
+    let mut csv = String::from("day,part,answer,duration_ns,status,error\n");
+    for record in records {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            record.day,
+            record.part,
+            csv_field(record.answer.as_deref().unwrap_or_default()),
+            record.duration_ns,
+            record.status,
+            csv_field(record.error.as_deref().unwrap_or_default()),
+        ));
+    }
+    csv
+}
+
+/// Quotes a field if it holds anything CSV treats specially, doubling any quotes inside it.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        String::from(value)
+    }
+}
+
+/// A JUnit report with a test suite per day and a test case per part.
+fn to_junit(records: &[Record]) -> String {
+    let seconds = |ns: u128| Duration::from_nanos(ns as u64).as_secs_f64();
+    let errors = |records: &[&Record]| {
+        records
+            .iter()
+            .filter(|record| record.status == Status::Error)
+            .count()
+    };
+
+    let mut days: Vec<u8> = records.iter().map(|record| record.day).collect();
+    days.dedup();
+
+    let all: Vec<&Record> = records.iter().collect();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"aoc23\" tests=\"{}\" failures=\"0\" errors=\"{}\" time=\"{:.6}\">\n",
+        all.len(),
+        errors(&all),
+        seconds(records.iter().map(|record| record.duration_ns).sum()),
+    ));
+
+    for day in days {
+        let suite: Vec<&Record> = records.iter().filter(|record| record.day == day).collect();
+        xml.push_str(&format!(
+            "  <testsuite name=\"day {:02}\" tests=\"{}\" failures=\"0\" errors=\"{}\" time=\"{:.6}\">\n",
+            day,
+            suite.len(),
+            errors(&suite),
+            seconds(suite.iter().map(|record| record.duration_ns).sum()),
+        ));
+
+        for record in suite {
+            xml.push_str(&format!(
+                "    <testcase classname=\"aoc23.day_{:02}\" name=\"part {}\" time=\"{:.6}\">\n",
+                day,
+                record.part,
+                seconds(record.duration_ns),
+            ));
+            if let Some(answer) = &record.answer {
+                xml.push_str(&format!(
+                    "      <system-out>{}</system-out>\n",
+                    xml_escape(answer)
+                ));
+            }
+            if let Some(error) = &record.error {
+                xml.push_str(&format!(
+                    "      <error message=\"{}\"/>\n",
+                    xml_escape(error)
+                ));
+            }
+            xml.push_str("    </testcase>\n");
+        }
+
+        xml.push_str("  </testsuite>\n");
+    }
+
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{csv_field, render, Format, Record};
+    use crate::error::Error;
+    use crate::solution::{Part, PartRun};
+    use std::time::Duration;
+
+    fn records() -> Vec<Record> {
+        vec![
+            Record::from_run(
+                3,
+                &PartRun {
+                    part: Part::One,
+                    answer: Ok(String::from("4361")),
+                    duration: Duration::from_micros(1500),
+                },
+            ),
+            Record::from_error(
+                4,
+                Part::Two,
+                &Error::malformed("missing \"|\", found <,>").at_line(2),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!(Ok(Format::Junit), "junit".parse::<Format>());
+        assert!("xml".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn test_csv_field() {
+        [
+            ("4361", "4361"),
+            ("", ""),
+            ("a,b", "\"a,b\""),
+            ("say \"hi\"", "\"say \"\"hi\"\"\""),
+        ]
+        .iter()
+        .for_each(|(input, want)| assert_eq!(*want, csv_field(input)));
+    }
+
+    #[test]
+    fn test_render() {
+        [
+            (Format::Text, "day 03: total - part one: 4361\n"),
+            (
+                Format::Csv,
+                r#"day,part,answer,duration_ns,status,error
+3,1,4361,1500000,ok,
+4,2,,0,error,"line 2: malformed record: missing ""|"", found <,>"
+"#,
+            ),
+            (
+                Format::Json,
+                r#"[
+  {
+    "day": 3,
+    "part": 1,
+    "answer": "4361",
+    "duration_ns": 1500000,
+    "status": "ok",
+    "error": null
+  },
+  {
+    "day": 4,
+    "part": 2,
+    "answer": null,
+    "duration_ns": 0,
+    "status": "error",
+    "error": "line 2: malformed record: missing \"|\", found <,>"
+  }
+]
+"#,
+            ),
+            (
+                Format::Junit,
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="aoc23" tests="2" failures="0" errors="1" time="0.001500">
+  <testsuite name="day 03" tests="1" failures="0" errors="0" time="0.001500">
+    <testcase classname="aoc23.day_03" name="part 1" time="0.001500">
+      <system-out>4361</system-out>
+    </testcase>
+  </testsuite>
+  <testsuite name="day 04" tests="1" failures="0" errors="1" time="0.000000">
+    <testcase classname="aoc23.day_04" name="part 2" time="0.000000">
+      <error message="line 2: malformed record: missing &quot;|&quot;, found &lt;,&gt;"/>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#,
+            ),
+        ]
+        .iter()
+        .for_each(|(format, want)| assert_eq!(*want, render(&records(), *format)));
+    }
+}
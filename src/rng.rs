@@ -0,0 +1,82 @@
+//! A small seeded random number generator, see [`Rng`].
+
+/// SplitMix64, a fast generator that gives the same sequence for the same seed on every platform.
+/// It is for making up puzzle inputs and test cases, not for anything that needs to be secure.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number from `0` up to but not including `bound`, which must not be zero.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        // Scaling rather than taking the remainder keeps the bias down to a negligible 1 in 2^64.
+        ((u128::from(self.next_u64()) * u128::from(bound)) >> 64) as u64
+    }
+
+    /// A number from `low` to `high` inclusive.
+    pub fn between(&mut self, low: u64, high: u64) -> u64 {
+        match (high - low).checked_add(1) {
+            Some(bound) => low + self.below(bound),
+            None => self.next_u64(),
+        }
+    }
+
+    /// Puts `items` in a random order, every order being equally likely.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i as u64 + 1) as usize);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rng;
+
+    #[test]
+    fn test_next_u64() {
+        // The first outputs of SplitMix64 seeded with 0, from the reference implementation.
+        let mut rng = Rng::new(0);
+        assert_eq!(0xe220_a839_7b1d_cdaf, rng.next_u64());
+        assert_eq!(0x6e78_9e6a_a1b9_65f4, rng.next_u64());
+        assert_eq!(0x06c4_5d18_8009_454f, rng.next_u64());
+    }
+
+    #[test]
+    fn test_between() {
+        let mut rng = Rng::new(7);
+        let mut seen = [false; 6];
+        for _ in 0..1000 {
+            let n = rng.between(3, 8);
+            assert!((3..=8).contains(&n), "{}", n);
+            seen[(n - 3) as usize] = true;
+        }
+        assert!(seen.iter().all(|seen| *seen));
+
+        assert_eq!(5, rng.between(5, 5));
+        rng.between(0, u64::MAX);
+    }
+
+    #[test]
+    fn test_shuffle() {
+        let mut items: Vec<u32> = (0..20).collect();
+        Rng::new(1).shuffle(&mut items);
+
+        assert_ne!((0..20).collect::<Vec<u32>>(), items);
+        items.sort_unstable();
+        assert_eq!((0..20).collect::<Vec<u32>>(), items);
+    }
+}
@@ -0,0 +1,321 @@
+//! The [`Solution`] trait implemented by every day and the [`registry`] of all of them.
+
+use crate::bench::Stage;
+use crate::day_01::vocabulary::Vocabulary;
+use crate::day_01::NoDigit;
+use crate::day_02::CubeSet;
+use crate::error::{Error, Result};
+use crate::{day_01, day_02, day_03, day_04};
+use std::fmt;
+use std::hint::black_box;
+use std::io::BufRead;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn number(&self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+
+    pub fn from_number(number: u8) -> Option<Self> {
+        match number {
+            1 => Some(Part::One),
+            2 => Some(Part::Two),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "part one"),
+            Part::Two => write!(f, "part two"),
+        }
+    }
+}
+
+/// Options that change how a day parses or solves its input, each day ignores the options that
+/// don't apply to it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Options {
+    /// Count with arbitrary precision integers from the start rather than only after overflowing.
+    pub big_integers: bool,
+    /// The bag day 2's games are played with, `None` for the bag from the puzzle.
+    pub bag: Option<CubeSet>,
+    /// What day 1 does with a line that has no digit in it.
+    pub no_digit: NoDigit,
+    /// The words day 1 reads as digits, `None` for the words from the puzzle.
+    pub vocabulary: Option<Vocabulary>,
+    /// Whether day 1 ignores the case of letters when reading words.
+    pub ignore_case: bool,
+    /// Whether day 1 reads the decimal digits of every script rather than only `0` to `9`.
+    pub unicode_digits: bool,
+}
+
+/// How an explanation is rendered, see [`Solution::explain`]. The day 2 commands read and write
+/// text or JSON with it too.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ExplainFormat {
+    /// A table with one row per record.
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for ExplainFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "text" => Ok(ExplainFormat::Text),
+            "json" => Ok(ExplainFormat::Json),
+            _ => Err(format!("unknown format '{}', expected text or json", value)),
+        }
+    }
+}
+
+/// A single day's puzzle, the input is parsed once and then shared by both parts.
+pub trait Solution {
+    const DAY: u8;
+
+    /// The example from the puzzle description, used when running with `--example`.
+    const EXAMPLE: &'static str;
+
+    type Input;
+    type Answer: fmt::Display;
+
+    /// Parses the puzzle input, errors should say where in the input they happened. Any options
+    /// the parts need are carried in the parsed input.
+    fn parse(input: &str, options: &Options) -> Result<Self::Input>;
+
+    fn part_one(input: &Self::Input) -> Result<Self::Answer>;
+
+    fn part_two(input: &Self::Input) -> Result<Self::Answer>;
+
+    /// How each record of the input contributes to the answers, for checking a wrong answer by
+    /// hand. `None` for days that can't explain themselves.
+    fn explain(_input: &Self::Input, _format: ExplainFormat) -> Option<String> {
+        None
+    }
+
+    /// Solves both parts in a single pass over `reader` without holding the whole input, for days
+    /// that can handle one record at a time. `None` for days that need all of the input at once.
+    fn stream(
+        _reader: &mut dyn BufRead,
+        _options: &Options,
+    ) -> Option<Result<(Self::Answer, Self::Answer)>> {
+        None
+    }
+}
+
+/// The answer, or the reason there isn't one, for a single part of a day.
+pub type PartResult = Result<String>;
+
+/// A part's answer along with how long it took to solve, not including the parse.
+#[derive(Debug)]
+pub struct PartRun {
+    pub part: Part,
+    pub answer: PartResult,
+    pub duration: Duration,
+}
+
+/// Object safe view of a [`Solution`] so that every day can be held in the same registry.
+pub trait Runner {
+    fn day(&self) -> u8;
+
+    fn example(&self) -> &'static str;
+
+    /// Parses the input once and solves each of the requested parts, an error is only returned
+    /// when the input could not be parsed.
+    fn run(&self, input: &str, parts: &[Part]) -> Result<Vec<PartRun>>;
+
+    /// Like [`Runner::run`] but returns how long the parse and each part took instead of the
+    /// answers, stopping at the first error.
+    fn time(&self, input: &str, parts: &[Part]) -> Result<Vec<(Stage, Duration)>>;
+
+    /// Parses the input and explains it, see [`Solution::explain`].
+    fn explain(&self, input: &str, format: ExplainFormat) -> Result<Option<String>>;
+
+    /// Like [`Runner::run`] but reads the input as it goes, see [`Solution::stream`]. Both parts
+    /// come from the same pass so each reports the time of the whole pass.
+    fn stream(&self, reader: &mut dyn BufRead, parts: &[Part]) -> Result<Vec<PartRun>>;
+}
+
+struct Registered<S: Solution> {
+    options: Options,
+    solution: PhantomData<S>,
+}
+
+impl<S: Solution> Runner for Registered<S> {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn example(&self) -> &'static str {
+        S::EXAMPLE
+    }
+
+    fn run(&self, input: &str, parts: &[Part]) -> Result<Vec<PartRun>> {
+        if input.trim().is_empty() {
+            return Err(Error::empty_input().in_day(S::DAY));
+        }
+
+        let parsed = S::parse(input, &self.options).map_err(|err| err.in_day(S::DAY))?;
+
+        Ok(parts
+            .iter()
+            .map(|&part| {
+                let start = Instant::now();
+                let answer = Self::solve(&parsed, part).map(|answer| answer.to_string());
+                PartRun {
+                    part,
+                    answer,
+                    duration: start.elapsed(),
+                }
+            })
+            .collect())
+    }
+
+    fn time(&self, input: &str, parts: &[Part]) -> Result<Vec<(Stage, Duration)>> {
+        let start = Instant::now();
+        let parsed =
+            black_box(S::parse(black_box(input), &self.options).map_err(|err| err.in_day(S::DAY))?);
+        let mut timings = vec![(Stage::Parse, start.elapsed())];
+
+        for &part in parts {
+            let start = Instant::now();
+            black_box(Self::solve(&parsed, part)?);
+            timings.push((Stage::Part(part), start.elapsed()));
+        }
+
+        Ok(timings)
+    }
+
+    fn explain(&self, input: &str, format: ExplainFormat) -> Result<Option<String>> {
+        if input.trim().is_empty() {
+            return Err(Error::empty_input().in_day(S::DAY));
+        }
+
+        let parsed = S::parse(input, &self.options).map_err(|err| err.in_day(S::DAY))?;
+        Ok(S::explain(&parsed, format))
+    }
+
+    fn stream(&self, reader: &mut dyn BufRead, parts: &[Part]) -> Result<Vec<PartRun>> {
+        let start = Instant::now();
+        let (part_one, part_two) = S::stream(reader, &self.options)
+            .unwrap_or_else(|| Err(Error::unsupported("the input can't be streamed")))
+            .map_err(|err| err.in_day(S::DAY))?;
+        let duration = start.elapsed();
+
+        Ok(parts
+            .iter()
+            .map(|&part| PartRun {
+                part,
+                answer: Ok(match part {
+                    Part::One => part_one.to_string(),
+                    Part::Two => part_two.to_string(),
+                }),
+                duration,
+            })
+            .collect())
+    }
+}
+
+impl<S: Solution> Registered<S> {
+    fn solve(parsed: &S::Input, part: Part) -> Result<S::Answer> {
+        match part {
+            Part::One => S::part_one(parsed),
+            Part::Two => S::part_two(parsed),
+        }
+        .map_err(|err| err.in_day(S::DAY).in_part(part))
+    }
+}
+
+fn register<S: Solution + 'static>(options: &Options) -> Box<dyn Runner> {
+    Box::new(Registered::<S> {
+        options: options.clone(),
+        solution: PhantomData,
+    })
+}
+
+/// Every available day in order with the default options.
+pub fn registry() -> Vec<Box<dyn Runner>> {
+    registry_with(&Options::default())
+}
+
+/// Every available day in order, adding a day only requires registering it here.
+pub fn registry_with(options: &Options) -> Vec<Box<dyn Runner>> {
+    vec![
+        register::<day_01::Day01>(options),
+        register::<day_02::Day02>(options),
+        register::<day_03::Day03>(options),
+        register::<day_04::Day04>(options),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{registry, registry_with, Options, Part};
+
+    #[test]
+    fn test_registry_days() {
+        let days: Vec<u8> = registry().iter().map(|runner| runner.day()).collect();
+
+        assert_eq!(vec![1, 2, 3, 4], days);
+    }
+
+    #[test]
+    fn test_registry_examples() {
+        let want = [
+            (1, "142", "142"),
+            (2, "8", "2286"),
+            (3, "4361", "467835"),
+            (4, "13", "30"),
+        ];
+
+        for (runner, (day, part_one, part_two)) in registry().iter().zip(want) {
+            let got: Vec<String> = runner
+                .run(runner.example(), &Part::ALL)
+                .unwrap()
+                .into_iter()
+                .map(|run| run.answer.unwrap())
+                .collect();
+
+            assert_eq!(vec![part_one, part_two], got, "day {}", day);
+        }
+    }
+
+    #[test]
+    fn test_registry_with_options() {
+        let options = Options {
+            big_integers: true,
+            ..Options::default()
+        };
+        let runner = registry_with(&options)
+            .into_iter()
+            .find(|runner| runner.day() == 4)
+            .unwrap();
+
+        let got: Vec<String> = runner
+            .run(runner.example(), &Part::ALL)
+            .unwrap()
+            .into_iter()
+            .map(|run| run.answer.unwrap())
+            .collect();
+
+        assert_eq!(vec!["13", "30"], got);
+    }
+}
@@ -0,0 +1,65 @@
+use aoc23::error::ErrorKind;
+use aoc23::solution::{registry, Options, Part, Solution};
+use aoc23::{day_01, day_02, day_03, day_04};
+
+#[test]
+fn test_day_01() {
+    assert_eq!(29, day_01::extract_digits_and_words("two1nine").unwrap());
+
+    let lines = day_01::Day01::parse(day_01::Day01::EXAMPLE, &Options::default()).unwrap();
+    assert_eq!(142, day_01::Day01::part_one(&lines).unwrap());
+}
+
+#[test]
+fn test_day_02() {
+    let parsed = day_02::Day02::parse(day_02::Day02::EXAMPLE, &Options::default()).unwrap();
+    let (games, bag) = (&parsed.games, &parsed.bag);
+
+    assert_eq!(5, games.len());
+    assert_eq!(48, games[0].minimum_set().power(&bag.colours()).unwrap());
+    assert_eq!(8, day_02::line_values_total(games, bag).unwrap());
+    assert_eq!(2286, day_02::line_powers_total(games, bag).unwrap());
+
+    let bag = day_02::parse_bag("20 red, 20 green, 20 blue").unwrap();
+    assert_eq!(15, day_02::line_values_total(games, &bag).unwrap());
+}
+
+#[test]
+fn test_day_03() {
+    let schem = day_03::create_padded_schematic(day_03::Day03::EXAMPLE).unwrap();
+
+    assert_eq!(4361, day_03::sum_part_numbers(&schem).unwrap());
+    assert_eq!(467835, day_03::sum_gear_ratios(&schem).unwrap());
+}
+
+#[test]
+fn test_day_04() {
+    let mut cards: Vec<day_04::ScratchCard> = vec![];
+    day_04::string_to_captures(day_04::Day04::EXAMPLE, &mut cards).unwrap();
+
+    assert_eq!(6, cards.len());
+    assert_eq!(
+        vec![1, 2, 3, 4, 5, 6],
+        cards.iter().map(|card| card.id).collect::<Vec<usize>>()
+    );
+    assert_eq!(13, day_04::sum_scratchcards(&cards).unwrap());
+    assert_eq!(30, day_04::sum_scratchcard_copies(&cards).unwrap());
+}
+
+#[test]
+fn test_errors() {
+    let runner = registry()
+        .into_iter()
+        .find(|runner| runner.day() == 3)
+        .unwrap();
+
+    let err = runner
+        .run("467..\n..*\n", &Part::ALL)
+        .map(|_| ())
+        .unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Malformed(_)));
+    assert_eq!((Some(3), Some(2)), (err.day(), err.line()));
+
+    let err = runner.run(" \n", &Part::ALL).map(|_| ()).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::EmptyInput));
+}